@@ -0,0 +1,191 @@
+//! Benchmarks of the event queue, the sweep, the intersection predicate and the sweep line.
+//!
+//! Generated inputs range from 1k to 100k segments, set `BENCH_MAX_SIZE=1000000` to include 1M.
+//! Save a baseline with `cargo bench -- --save-baseline before` and compare a change against it
+//! with `cargo bench -- --baseline before`.
+
+use std::{env, path::Path, str::FromStr};
+
+use cg_3_sweep_line::{
+    arena::Arena,
+    event_queue::EventQueue,
+    generate::{self, Distribution, GenerateOptions},
+    geometry::Line,
+    input::read_file,
+    parallel,
+    sweep_line::SweepLine,
+};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+/// Valid data sets shipped in `data/`.
+const DATA_SETS: [&str; 3] = [
+    "data/s_1000_10.dat",
+    "data/gen_1000_10.dat",
+    "data/gen_10000_10.dat",
+];
+
+fn sizes() -> impl Iterator<Item = usize> {
+    let max_size = env::var("BENCH_MAX_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(100_000);
+    SIZES.into_iter().filter(move |&size| size <= max_size)
+}
+
+/// Intersection densities, as segments per unit of the side length of the area.
+///
+/// The area grows with the number of segments, so the intersections per segment stay about the same across sizes.
+const DENSITIES: [(&str, f64); 2] = [("sparse", 0.05), ("dense", 0.5)];
+
+fn uniform(count: usize, density: f64) -> Vec<Line> {
+    generate::generate(&GenerateOptions {
+        distribution: Distribution::Uniform,
+        count,
+        size: (count as f64).sqrt() / density,
+        seed: 1,
+        ..Default::default()
+    })
+    .expect("the benchmark data can be generated")
+}
+
+fn event_queue_new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_queue_new");
+    group.sample_size(10);
+    for size in sizes() {
+        let lines = uniform(size, DENSITIES[0].1);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &lines, |b, lines| {
+            b.iter_batched(|| lines.clone(), EventQueue::new, BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+fn sweep(c: &mut Criterion) {
+    for (name, density) in DENSITIES {
+        let mut group = c.benchmark_group(format!("sweep/{}", name));
+        group.sample_size(10);
+        for size in sizes() {
+            let lines = uniform(size, density);
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &lines, |b, lines| {
+                b.iter_batched(
+                    || EventQueue::new(lines.clone()),
+                    EventQueue::sweep,
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+        group.finish();
+    }
+}
+
+fn parallel_sweep(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sweep/slabs");
+    group.sample_size(10);
+    let size = sizes().last().unwrap_or(SIZES[0]);
+    let lines = uniform(size, DENSITIES[1].1);
+    group.throughput(Throughput::Elements(size as u64));
+    for slabs in [1, 2, 4, 8] {
+        group.bench_with_input(BenchmarkId::from_parameter(slabs), &slabs, |b, &slabs| {
+            b.iter(|| parallel::sweep(&lines, slabs))
+        });
+    }
+    group.finish();
+}
+
+fn sweep_data(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sweep/data");
+    for file in DATA_SETS {
+        let lines = read_file(file).unwrap();
+        let name = Path::new(file).file_name().unwrap().to_str().unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(name), &lines, |b, lines| {
+            b.iter_batched(
+                || EventQueue::new(lines.clone()),
+                EventQueue::sweep,
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn line_intersection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line_intersection");
+    let cases = [
+        ("crossing", "0 0 4 4", "0 4 4 0"),
+        // rejected by the first orientation test
+        ("disjoint", "0 0 4 1", "0 2 4 3"),
+        ("touching", "0 0 4 4", "2 2 4 0"),
+        ("nearly_parallel", "0 0 1000 1", "0 0.001 1000 0.999"),
+    ];
+    for (name, line, other) in cases {
+        let line = Line::from_str(line).unwrap();
+        let other = Line::from_str(other).unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(&line).intersection(black_box(&other)))
+        });
+    }
+    group.finish();
+}
+
+/// Sweep line with `size` segments spanning `[0, 1]`, sorted at `x = 0.5`.
+fn sweep_line(size: usize) -> (SweepLine, Arena) {
+    let segments: Arena = (0..size)
+        .map(|i| {
+            let y = i as f64;
+            Line::from_str(&format!("0 {} 1 {}", y, y + 0.5 * (i % 3) as f64)).unwrap()
+        })
+        .collect();
+    let mut sweep_line = SweepLine::new();
+    for (id, line) in segments.iter() {
+        sweep_line.insert(line.y(0.5), id);
+    }
+    (sweep_line, segments)
+}
+
+fn sweep_line_operations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sweep_line");
+    for size in [100, 1_000, 10_000] {
+        let (mut status, mut segments) = sweep_line(size);
+        let middle = status.elements[size / 2].segment;
+        let extra = Line::from_str(&format!("0 {} 1 {}", size as f64 / 2.0 + 0.25, 0.0)).unwrap();
+        let y = extra.y(0.5);
+        let extra = segments.push(extra);
+
+        group.bench_with_input(
+            BenchmarkId::new("insert_remove", size),
+            &extra,
+            |b, &segment| {
+                b.iter(|| {
+                    status.insert(y, segment);
+                    status.remove(segment);
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get_neighbors", size),
+            &middle,
+            |b, &segment| b.iter(|| status.get_neighbors(black_box(segment))),
+        );
+        group.bench_with_input(BenchmarkId::new("update", size), &0.5, |b, &x| {
+            b.iter(|| status.update(black_box(x), &segments))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    event_queue_new,
+    sweep,
+    parallel_sweep,
+    sweep_data,
+    line_intersection,
+    sweep_line_operations
+);
+criterion_main!(benches);
@@ -0,0 +1,969 @@
+//! Choice between the intersection backends, which all find the same intersections rounded to 9 decimals, and
+//! [`run`], which finds the intersections of one input with the backend and the outputs asked for.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+    thread,
+};
+
+use log::{info, warn};
+
+use crate::{
+    animation::SweepRecorder,
+    arc::{self, Arc},
+    arena::{Arena, SegmentId},
+    brute_force,
+    collect::{CollectOptions, Input, Segments},
+    event_queue::{Event, EventQueue, InvalidSegment},
+    geometry::{Bounds, Line, Point},
+    grid,
+    input::InputFormat,
+    observer::{
+        DiscardReason, IntersectionCounter, IntersectionPairs, SegmentCounts, SweepObserver,
+        SweepStats,
+    },
+    orthogonal, parallel,
+    polyline::{self, Crossings, Overlap, Polyline},
+    sweep_line::SweepLine,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Bentley-Ottmann sweep, see [`EventQueue::sweep`]
+    #[default]
+    Sweep,
+    /// Uniform grid, see [`grid::intersections`]
+    Grid,
+    /// Every pair of segments, see [`brute_force::intersections`]
+    Brute,
+    /// Sweep for horizontal and vertical segments only, see [`orthogonal::intersections`]
+    Orthogonal,
+    /// One of the others, picked by [`Algorithm::choose`]
+    Auto,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sweep" => Ok(Algorithm::Sweep),
+            "grid" => Ok(Algorithm::Grid),
+            "brute" => Ok(Algorithm::Brute),
+            "orthogonal" => Ok(Algorithm::Orthogonal),
+            "auto" => Ok(Algorithm::Auto),
+            _ => Err(format!("Unknown algorithm: {}", s)),
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Algorithm::Sweep => "sweep",
+            Algorithm::Grid => "grid",
+            Algorithm::Brute => "brute",
+            Algorithm::Orthogonal => "orthogonal",
+            Algorithm::Auto => "auto",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether there are segments and all of them are horizontal or vertical.
+pub fn all_axis_parallel(lines: &[Line]) -> bool {
+    !lines.is_empty() && lines.iter().all(orthogonal::is_axis_parallel)
+}
+
+/// Below this many segments, testing every pair is fastest.
+const BRUTE_FORCE_LIMIT: usize = 64;
+
+impl Algorithm {
+    /// Picks the fastest backend for the segments from their lengths.
+    ///
+    /// Axis-parallel segments always use the orthogonal sweep. Otherwise the grid wins if the segments are short
+    /// compared to the distance between them, and none is long enough to fall into many cells. Long or densely packed
+    /// segments are left to the sweep.
+    pub fn choose(lines: &[Line]) -> Algorithm {
+        if all_axis_parallel(lines) {
+            return Algorithm::Orthogonal;
+        }
+        if lines.len() < BRUTE_FORCE_LIMIT {
+            return Algorithm::Brute;
+        }
+        let Some(bounds) = Bounds::of_lines(lines) else {
+            return Algorithm::Brute;
+        };
+
+        let extents = lines.iter().map(grid::extent);
+        let mean_extent = extents.clone().sum::<f64>() / lines.len() as f64;
+        let max_extent = extents.fold(0.0, f64::max);
+        let spacing = (bounds.width() * bounds.height() / lines.len() as f64).sqrt();
+
+        if mean_extent <= 2.0 * spacing && max_extent <= 4.0 * grid::cell_size(lines) {
+            Algorithm::Grid
+        } else {
+            Algorithm::Sweep
+        }
+    }
+
+    /// The algorithm which [`Algorithm::intersections`] runs for the segments.
+    ///
+    /// The sweep is replaced by the orthogonal sweep for axis-parallel segments, as it cannot handle vertical segments.
+    pub fn resolve(self, lines: &[Line]) -> Algorithm {
+        match self {
+            Algorithm::Auto => Algorithm::choose(lines),
+            Algorithm::Sweep if all_axis_parallel(lines) => Algorithm::Orthogonal,
+            algorithm => algorithm,
+        }
+    }
+
+    /// Intersections of the segments, the sweep panics for segments violating its requirements.
+    pub fn intersections(self, lines: &[Line]) -> BTreeSet<Point> {
+        match self.resolve(lines) {
+            Algorithm::Sweep => EventQueue::new(lines.to_vec()).sweep(),
+            Algorithm::Grid => grid::intersections(lines),
+            Algorithm::Orthogonal => orthogonal::intersections(lines),
+            Algorithm::Brute | Algorithm::Auto => brute_force::intersections(lines),
+        }
+    }
+
+    /// Like [`Algorithm::intersections`], with the ids of all segments through each point, which are their positions
+    /// in `lines`.
+    ///
+    /// The sweep returns `None`, it reports the segments of every intersection to its observers instead.
+    pub fn crossings(self, lines: &[Line]) -> Option<BTreeMap<Point, Vec<SegmentId>>> {
+        match self.resolve(lines) {
+            Algorithm::Sweep => None,
+            Algorithm::Grid => Some(grid::crossings(lines)),
+            Algorithm::Orthogonal => Some(orthogonal::crossings(lines)),
+            Algorithm::Brute | Algorithm::Auto => Some(brute_force::crossings(lines)),
+        }
+    }
+}
+
+/// What [`run`] reports besides the intersection points, and how it finds them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunOptions {
+    pub algorithm: Algorithm,
+    /// Sweep in this many vertical slabs on parallel threads, 0 for one per CPU, see [`parallel::sweep`]
+    pub slabs: Option<usize>,
+    /// Sweep along this direction in degrees instead of the x axis
+    pub direction: Option<f64>,
+    /// Keep the segments for the output in [`Intersections::segments`]; the pairs, counts and the animation keep
+    /// them anyway
+    pub keep_lines: bool,
+    /// Name two segments of every intersection, see [`Intersections::pairs`]
+    pub pairs: bool,
+    /// Count the intersections of every segment, see [`Intersections::counts`]
+    pub counts: bool,
+    /// Record the steps of the sweep for an animation of at most this many segments
+    pub record: Option<usize>,
+    /// Count the events of the sweep, see [`Intersections::stats`]
+    pub stats: bool,
+    /// Only count the intersections of the sequential sweep instead of keeping them
+    pub count_only: bool,
+}
+
+impl RunOptions {
+    /// The output which needs the single steps of the sweep, which only the event queue reports.
+    fn steps(&self) -> Option<&'static str> {
+        match (self.record, self.stats) {
+            (Some(_), _) => Some("the animation"),
+            (None, true) => Some("the statistics"),
+            (None, false) => None,
+        }
+    }
+
+    /// Whether an output needs more than the intersection points.
+    fn observed(&self) -> bool {
+        self.pairs || self.counts || self.record.is_some() || self.stats
+    }
+
+    /// The options for the input format, before it is read.
+    ///
+    /// The paths of WKT and GeoJSON features are swept by the polyline sweep and ignore the backend options. The
+    /// parallel sweep is replaced by the sequential one for outputs which need more than the points, and `auto` by the
+    /// sweep for outputs which need its steps. Ignored options are logged, options which cannot be combined fail.
+    pub fn resolve(self, format: InputFormat) -> Result<RunOptions, RunError> {
+        if matches!(format, InputFormat::Wkt | InputFormat::GeoJson) {
+            if self.record.is_some() {
+                return Err(RunError::Unsupported(
+                    "features cannot be animated, their paths are swept by the polyline sweep"
+                        .to_string(),
+                ));
+            }
+            let ignored: Vec<_> = [
+                ("the algorithm", self.algorithm != Algorithm::Sweep),
+                ("the slabs", self.slabs.is_some()),
+                ("the direction", self.direction.is_some()),
+                ("the statistics", self.stats),
+            ]
+            .into_iter()
+            .filter_map(|(option, given)| given.then_some(option))
+            .collect();
+            if !ignored.is_empty() {
+                warn!(
+                    "Ignoring {}, the paths of features are swept by the polyline sweep",
+                    ignored.join(", ")
+                );
+            }
+            return Ok(RunOptions {
+                algorithm: Algorithm::Sweep,
+                slabs: None,
+                direction: None,
+                stats: false,
+                ..self
+            });
+        }
+
+        if self.direction.is_some() && self.record.is_some() {
+            return Err(RunError::Unsupported(
+                "a direction does not support the animation, the frames show a sweep along the x axis"
+                    .to_string(),
+            ));
+        }
+        let slabs = match self.slabs {
+            Some(_) if self.direction.is_some() => {
+                warn!("Ignoring the slabs, the parallel sweep only sweeps along the x axis");
+                None
+            }
+            Some(_) if self.observed() => {
+                warn!("Ignoring the slabs, the output needs the sequential sweep");
+                None
+            }
+            Some(0) => Some(thread::available_parallelism().map_or(1, |slabs| slabs.get())),
+            slabs => slabs,
+        };
+        let algorithm = match (self.algorithm, self.steps()) {
+            (algorithm @ (Algorithm::Grid | Algorithm::Brute), Some(steps)) => {
+                return Err(RunError::Unsupported(format!(
+                    "the {} algorithm does not support {}",
+                    algorithm, steps
+                )));
+            }
+            (Algorithm::Auto, Some(_)) => Algorithm::Sweep,
+            (algorithm, _) => algorithm,
+        };
+        Ok(RunOptions {
+            slabs,
+            algorithm,
+            ..self
+        })
+    }
+
+    /// How to collect the input for these options, after [`RunOptions::resolve`].
+    pub fn collect_options(&self, skip_invalid: bool) -> CollectOptions {
+        // the other algorithms and the parallel sweep work on the segments in memory
+        let in_memory = self.slabs.is_some() || self.algorithm != Algorithm::Sweep;
+        CollectOptions {
+            skip_invalid,
+            keep_lines: self.keep_lines
+                || self.pairs
+                || self.counts
+                || self.record.is_some()
+                || in_memory,
+            direction: self.direction,
+            orthogonal: matches!(
+                self.algorithm,
+                Algorithm::Sweep | Algorithm::Auto | Algorithm::Orthogonal
+            ),
+            timed: self.stats,
+        }
+    }
+}
+
+/// Why [`run`] could not find the intersections of an input.
+#[derive(Debug)]
+pub enum RunError {
+    /// A segment violates the requirements of the sweep
+    Invalid(InvalidSegment),
+    /// The options cannot be combined with each other or the input, the message says why
+    Unsupported(String),
+    /// The input has more segments than the animation draws
+    TooManySegments { limit: usize, count: usize },
+    /// Segments of two feature paths overlap, by their positions and features
+    Overlap {
+        segments: [usize; 2],
+        features: [String; 2],
+    },
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Invalid(error) => write!(f, "{}", error),
+            RunError::Unsupported(message) => write!(f, "{}", message),
+            RunError::TooManySegments { limit, count } => write!(
+                f,
+                "the animation draws at most {} segments, found {}",
+                limit, count
+            ),
+            RunError::Overlap { segments, features } => write!(
+                f,
+                "segment {} of feature {} overlaps segment {} of feature {}, which has no single crossing point",
+                segments[0], features[0], segments[1], features[1]
+            ),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+/// The intersections of one input and what [`RunOptions`] asked for besides them.
+pub struct Intersections {
+    /// The backend which found the intersections
+    pub algorithm: Algorithm,
+    /// The intersection points, empty if they were only counted
+    pub points: BTreeSet<Point>,
+    /// Number of intersections, also if they were only counted
+    pub found: usize,
+    /// Number of segments swept
+    pub segment_count: usize,
+    /// The segments by id, if kept
+    pub segments: Segments,
+    /// Two of the segments through every point
+    pub pairs: Option<BTreeMap<Point, [SegmentId; 2]>>,
+    pub counts: Option<SegmentCounts>,
+    pub recorder: Option<SweepRecorder>,
+    pub stats: Option<SweepStats>,
+    arcs: Vec<Arc>,
+    polylines: Vec<Polyline>,
+    first_segments: Vec<usize>,
+}
+
+impl Intersections {
+    /// The intersections of the same input found by brute force, to verify the result.
+    pub fn brute_force(&self) -> Result<BTreeSet<Point>, RunError> {
+        Ok(match self.algorithm {
+            _ if !self.polylines.is_empty() => polyline::brute_force(&self.polylines)
+                .map_err(|overlap| feature_overlap(overlap, &self.first_segments, &self.segments))?
+                .points(),
+            // segments on the same line make the general brute force panic
+            Algorithm::Orthogonal => orthogonal::brute_force(&self.segments.lines),
+            _ if !self.arcs.is_empty() => arc::brute_force(&self.segments.lines, &self.arcs),
+            _ => brute_force::intersections(&self.segments.lines),
+        })
+    }
+}
+
+/// The overlap of two feature paths by the positions of the segments in the input.
+fn feature_overlap(overlap: Overlap, first_segments: &[usize], segments: &Segments) -> RunError {
+    let id = |(polyline, segment)| first_segments[polyline] + segment;
+    let (a, b) = (id(overlap.a), id(overlap.b));
+    RunError::Overlap {
+        segments: [a, b],
+        features: [a, b].map(|id| segments.features[id].clone()),
+    }
+}
+
+/// The segments through every crossing of the feature paths, by their position in the input.
+fn feature_crossings(
+    crossings: &Crossings,
+    first_segments: &[usize],
+) -> BTreeMap<Point, Vec<SegmentId>> {
+    let mut found: BTreeMap<Point, Vec<SegmentId>> = BTreeMap::new();
+    for crossing in crossings.between.iter().chain(&crossings.self_crossings) {
+        let ids = found.entry(crossing.point.clone()).or_default();
+        for location in [&crossing.a, &crossing.b] {
+            ids.push(SegmentId::new(
+                first_segments[location.polyline] + location.segment,
+            ));
+        }
+    }
+    for ids in found.values_mut() {
+        ids.sort_unstable();
+        ids.dedup();
+    }
+    found
+}
+
+/// The observers of one run, each only if its output was asked for.
+#[derive(Default)]
+struct Observers {
+    recorder: Option<SweepRecorder>,
+    counter: Option<IntersectionCounter>,
+    pairs: Option<IntersectionPairs>,
+    stats: Option<SweepStats>,
+}
+
+impl Observers {
+    fn each(&mut self, mut f: impl FnMut(&mut dyn SweepObserver)) {
+        f(&mut self.recorder);
+        f(&mut self.counter);
+        f(&mut self.pairs);
+        f(&mut self.stats);
+    }
+}
+
+impl SweepObserver for Observers {
+    fn event_popped(&mut self, event: &Event) {
+        self.each(|observer| observer.event_popped(event));
+    }
+
+    fn segment_inserted(&mut self, segment: SegmentId, segments: &Arena) {
+        self.each(|observer| observer.segment_inserted(segment, segments));
+    }
+
+    fn segment_removed(&mut self, segment: SegmentId, segments: &Arena) {
+        self.each(|observer| observer.segment_removed(segment, segments));
+    }
+
+    fn segments_swapped(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        self.each(|observer| observer.segments_swapped(point, segment, other_segment, segments));
+    }
+
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        self.each(|observer| {
+            observer.intersection_scheduled(point, segment, other_segment, segments)
+        });
+    }
+
+    fn intersection_discarded(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        self.each(|observer| {
+            observer.intersection_discarded(point, segment, other_segment, segments, reason)
+        });
+    }
+
+    fn event_handled(&mut self, event: &Event, sweep_line: &SweepLine, segments: &Arena) {
+        self.each(|observer| observer.event_handled(event, sweep_line, segments));
+    }
+}
+
+/// Finds the intersections of the input with the backend of the options, after [`RunOptions::resolve`].
+///
+/// Axis-parallel input is swept by the orthogonal sweep unless the output needs the steps of the sweep, arcs by
+/// the arc sweep or brute force, and the paths of features by the polyline sweep. The sweeps report the segments
+/// of every intersection to observers, the other backends and the polyline sweep return them with every point.
+pub fn run(mut input: Input, options: &RunOptions) -> Result<Intersections, RunError> {
+    let steps = options.steps();
+    if !input.arcs.is_empty() {
+        if options.observed() || options.direction.is_some() {
+            return Err(RunError::Unsupported(
+                "arcs cannot be combined with a direction, or outputs which need the steps or the segments of every intersection"
+                    .to_string(),
+            ));
+        }
+        if options.slabs.is_some() {
+            warn!("Ignoring the slabs, arcs are swept sequentially");
+        }
+        // the segments collected for the orthogonal sweep are swept with the arcs
+        input.enqueue_orthogonal();
+        info!("Sweeping {} arcs with the segments", input.arcs.len());
+    }
+    // the orthogonal sweep has no single steps to report, so these outputs need the event queue
+    if let (Some(steps), Some(_)) = (steps, &input.orthogonal) {
+        if options.algorithm == Algorithm::Orthogonal {
+            return Err(RunError::Unsupported(format!(
+                "the orthogonal sweep does not support {}",
+                steps
+            )));
+        }
+        input.enqueue_orthogonal();
+        if let Some(error) = input.invalid {
+            return Err(RunError::Unsupported(format!(
+                "{}, and the orthogonal sweep for axis-parallel input does not support {}",
+                error, steps
+            )));
+        }
+    }
+    if options.algorithm == Algorithm::Orthogonal && input.orthogonal.is_none() {
+        return Err(RunError::Unsupported(
+            "the orthogonal sweep needs horizontal and vertical segments".to_string(),
+        ));
+    }
+    if let Some(error) = input.invalid {
+        return Err(RunError::Invalid(error));
+    }
+    if input.skipped > 0 {
+        warn!("Skipped {} invalid segments", input.skipped);
+    }
+    let (segments, segment_count, orthogonal) = match input.orthogonal {
+        Some(lines) if !lines.lines.is_empty() => {
+            let count = lines.lines.len();
+            (lines, count, true)
+        }
+        _ => (input.segments, input.count, false),
+    };
+    if let Some(limit) = options.record.filter(|&limit| segment_count > limit) {
+        return Err(RunError::TooManySegments {
+            limit,
+            count: segment_count,
+        });
+    }
+
+    let features = !input.polylines.is_empty();
+    let arcs = !input.arcs.is_empty();
+    let algorithm = match options.algorithm {
+        _ if orthogonal => Algorithm::Orthogonal,
+        Algorithm::Grid if arcs => {
+            warn!("Ignoring the grid algorithm, arcs need the sweep or brute force");
+            Algorithm::Sweep
+        }
+        Algorithm::Auto if arcs => Algorithm::Sweep,
+        Algorithm::Auto => Algorithm::choose(&segments.lines),
+        algorithm => algorithm,
+    };
+    if features {
+        info!(
+            "Sweeping {} feature paths as polylines",
+            input.polylines.len()
+        );
+    } else if options.algorithm == Algorithm::Auto {
+        info!("Picked the {} algorithm", algorithm);
+    } else if options.algorithm != algorithm && algorithm == Algorithm::Orthogonal {
+        info!("All segments are axis-parallel, using the orthogonal sweep");
+    }
+
+    let mut observers = Observers {
+        recorder: options
+            .record
+            .map(|_| SweepRecorder::new(segments.lines.clone())),
+        counter: options.counts.then(IntersectionCounter::default),
+        pairs: options.pairs.then(IntersectionPairs::default),
+        stats: options.stats.then(SweepStats::default),
+    };
+    // counting with the sequential sweep does not keep the intersections
+    let mut counted = None;
+    let mut crossings = None;
+    let points = match (algorithm, options.slabs) {
+        _ if features => {
+            let found = polyline::crossings(&input.polylines)
+                .map_err(|overlap| feature_overlap(overlap, &input.first_segments, &segments))?;
+            let found = feature_crossings(&found, &input.first_segments);
+            crossings.insert(found).keys().cloned().collect()
+        }
+        (Algorithm::Brute, _) if arcs => {
+            arc::brute_force(input.queue.segments().lines(), &input.arcs)
+        }
+        _ if arcs => arc::intersections(input.queue.segments().lines(), &input.arcs),
+        (Algorithm::Sweep, Some(slabs)) => parallel::sweep(&segments.lines, slabs),
+        (Algorithm::Sweep, None) => match input.directed {
+            Some(directed) => directed.sweep_with_observer(&mut observers),
+            None if options.count_only => {
+                counted = Some(input.queue.count_with_observer(&mut observers));
+                BTreeSet::new()
+            }
+            None => input.queue.sweep_with_observer(&mut observers),
+        },
+        (algorithm, _) if options.observed() => {
+            let found = algorithm
+                .crossings(&segments.lines)
+                .expect("the sweep is handled above");
+            crossings.insert(found).keys().cloned().collect()
+        }
+        (algorithm, _) => algorithm.intersections(&segments.lines),
+    };
+
+    let mut pairs: Option<BTreeMap<_, _>> = observers.pairs.map(|pairs| {
+        pairs
+            .pairs
+            .into_iter()
+            .map(|(point, (id, other_id))| (point, [id, other_id]))
+            .collect()
+    });
+    let counts = match crossings {
+        Some(crossings) => {
+            let mut counts = vec![0; segments.lines.len()];
+            for (point, ids) in crossings {
+                for &id in &ids {
+                    counts[id.index()] += 1;
+                }
+                if let Some(pairs) = &mut pairs {
+                    pairs.insert(point, [ids[0], ids[1]]);
+                }
+            }
+            options.counts.then_some(SegmentCounts { counts })
+        }
+        None => observers
+            .counter
+            .map(|counter| counter.segment_counts(segments.lines.len())),
+    };
+    Ok(Intersections {
+        algorithm,
+        found: counted.unwrap_or(points.len()),
+        points,
+        segment_count,
+        segments,
+        pairs,
+        counts,
+        recorder: observers.recorder,
+        stats: observers.stats,
+        arcs: input.arcs,
+        polylines: input.polylines,
+        first_segments: input.first_segments,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        collect,
+        generate::{generate, Distribution, GenerateOptions},
+        input::{read_file, SegmentReader},
+    };
+
+    #[test]
+    fn test_same_result() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let expected = Algorithm::Sweep.intersections(&lines);
+        assert_eq!(expected.len(), 796);
+        for algorithm in [Algorithm::Grid, Algorithm::Brute, Algorithm::Auto] {
+            assert_eq!(algorithm.intersections(&lines), expected, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_crossings() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        assert!(Algorithm::Sweep.crossings(&lines).is_none());
+        let expected = Algorithm::Brute.crossings(&lines).unwrap();
+        assert!(expected.keys().eq(&Algorithm::Sweep.intersections(&lines)));
+        for algorithm in [Algorithm::Grid, Algorithm::Auto] {
+            assert_eq!(
+                algorithm.crossings(&lines).as_ref(),
+                Some(&expected),
+                "{}",
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn test_choose() {
+        let generated = |distribution, count| {
+            generate(&GenerateOptions {
+                distribution,
+                count,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        assert_eq!(
+            Algorithm::choose(&generated(Distribution::Uniform, 10)),
+            Algorithm::Brute
+        );
+        assert_eq!(
+            Algorithm::choose(&generated(Distribution::Uniform, 2000)),
+            Algorithm::Grid
+        );
+        // long segments spanning the whole area
+        assert_eq!(
+            Algorithm::choose(&generated(Distribution::Grid, 500)),
+            Algorithm::Sweep
+        );
+        let orthogonal: Vec<_> = ["0 1 4 1", "2 0 2 3"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        assert_eq!(Algorithm::choose(&orthogonal), Algorithm::Orthogonal);
+        assert_eq!(Algorithm::Sweep.resolve(&orthogonal), Algorithm::Orthogonal);
+        assert_eq!(Algorithm::Sweep.intersections(&orthogonal).len(), 1);
+        assert_eq!(Algorithm::Auto.resolve(&[]), Algorithm::Brute);
+        assert_eq!(Algorithm::Sweep.resolve(&[]), Algorithm::Sweep);
+        assert_eq!(Algorithm::Grid.resolve(&[]), Algorithm::Grid);
+    }
+
+    fn collect(text: &str, format: InputFormat, options: &RunOptions) -> Input {
+        let reader = SegmentReader::new("test", text.as_bytes());
+        collect::read(reader, format, options.collect_options(false)).unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let stats = RunOptions {
+            algorithm: Algorithm::Grid,
+            stats: true,
+            ..Default::default()
+        };
+        let error = stats.resolve(InputFormat::Segments).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the grid algorithm does not support the statistics"
+        );
+        let auto = RunOptions {
+            algorithm: Algorithm::Auto,
+            record: Some(10),
+            ..Default::default()
+        };
+        let resolved = auto.resolve(InputFormat::Segments).unwrap();
+        assert_eq!(resolved.algorithm, Algorithm::Sweep);
+        assert!(resolved.collect_options(false).keep_lines);
+
+        // only the sequential sweep names the segments of every intersection
+        let slabs = RunOptions {
+            slabs: Some(0),
+            ..Default::default()
+        };
+        let resolved = slabs.resolve(InputFormat::Segments).unwrap();
+        assert!(resolved.slabs.unwrap() > 0);
+        assert!(resolved.collect_options(false).keep_lines);
+        let pairs = RunOptions {
+            pairs: true,
+            ..slabs
+        };
+        assert_eq!(pairs.resolve(InputFormat::Segments).unwrap().slabs, None);
+
+        // features ignore the backend options
+        let features = RunOptions {
+            algorithm: Algorithm::Grid,
+            direction: Some(30.0),
+            stats: true,
+            ..slabs
+        };
+        assert_eq!(
+            features.resolve(InputFormat::Wkt).unwrap(),
+            RunOptions::default()
+        );
+        let record = RunOptions {
+            record: Some(10),
+            ..Default::default()
+        };
+        assert!(record.resolve(InputFormat::GeoJson).is_err());
+        let directed = RunOptions {
+            direction: Some(30.0),
+            ..record
+        };
+        assert!(directed.resolve(InputFormat::Segments).is_err());
+
+        let count = RunOptions {
+            count_only: true,
+            ..Default::default()
+        }
+        .collect_options(true);
+        assert!(!count.keep_lines && count.orthogonal && count.skip_invalid);
+    }
+
+    #[test]
+    fn test_run() {
+        let text = "0 0 4 4\n0 4 4 0\n0 2 4 2.5\n";
+        for algorithm in [Algorithm::Sweep, Algorithm::Grid, Algorithm::Brute] {
+            let options = RunOptions {
+                algorithm,
+                pairs: true,
+                counts: true,
+                ..Default::default()
+            };
+            let found = run(collect(text, InputFormat::Segments, &options), &options).unwrap();
+            assert_eq!((found.found, found.segment_count), (3, 3), "{}", algorithm);
+            assert_eq!(found.counts.as_ref().unwrap().counts, [2, 2, 2]);
+            assert_eq!(found.pairs.as_ref().unwrap().len(), 3);
+            assert_eq!(found.brute_force().unwrap(), found.points);
+        }
+        // grid and brute force name the segments of every point themselves
+        for algorithm in [Algorithm::Grid, Algorithm::Brute] {
+            let options = RunOptions {
+                algorithm,
+                pairs: true,
+                ..Default::default()
+            };
+            let found = run(collect(text, InputFormat::Segments, &options), &options).unwrap();
+            let pairs: Vec<_> = found.pairs.unwrap().into_values().collect();
+            assert_eq!(
+                pairs,
+                [[1, 2], [0, 1], [0, 2]].map(|ids| ids.map(SegmentId::new))
+            );
+        }
+
+        // counting keeps no points
+        let count = RunOptions {
+            count_only: true,
+            stats: true,
+            ..Default::default()
+        };
+        let found = run(collect(text, InputFormat::Segments, &count), &count).unwrap();
+        assert_eq!((found.found, found.points.len()), (3, 0));
+        assert!(found.stats.unwrap().events > 0);
+        let record = RunOptions {
+            record: Some(2),
+            ..Default::default()
+        };
+        let error = run(collect(text, InputFormat::Segments, &record), &record);
+        assert!(matches!(
+            error,
+            Err(RunError::TooManySegments { limit: 2, count: 3 })
+        ));
+
+        // axis-parallel input is swept by the orthogonal sweep, which has no steps
+        let orthogonal = "0 1 4 1\n2 0 2 3\n";
+        let options = RunOptions::default();
+        let found = run(
+            collect(orthogonal, InputFormat::Segments, &options),
+            &options,
+        )
+        .unwrap();
+        assert_eq!((found.algorithm, found.found), (Algorithm::Orthogonal, 1));
+        let stats = RunOptions {
+            stats: true,
+            ..options
+        };
+        let error = run(collect(orthogonal, InputFormat::Segments, &stats), &stats)
+            .err()
+            .unwrap();
+        assert!(error.to_string().ends_with(
+            "the orthogonal sweep for axis-parallel input does not support the statistics"
+        ));
+
+        let arcs = "0 0 4 4\narc 2 2 1 0 0\n";
+        let found = run(collect(arcs, InputFormat::Segments, &options), &options).unwrap();
+        assert_eq!(found.found, 2);
+        let pairs = RunOptions {
+            pairs: true,
+            ..options
+        };
+        let error = run(collect(arcs, InputFormat::Segments, &pairs), &pairs);
+        assert!(matches!(error, Err(RunError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_run_observed() {
+        // the orthogonal sweep allows vertical segments and shared end points, and names the segments itself
+        let options = RunOptions {
+            pairs: true,
+            counts: true,
+            ..Default::default()
+        };
+        let orthogonal = "0 1 4 1\n2 0 2 3\n2 3 5 3\n";
+        let found = run(
+            collect(orthogonal, InputFormat::Segments, &options),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(found.algorithm, Algorithm::Orthogonal);
+        assert_eq!(found.counts.unwrap().counts, [1, 2, 1]);
+        let pairs: Vec<_> = found.pairs.unwrap().into_values().collect();
+        assert_eq!(pairs, [[0, 1], [1, 2]].map(|ids| ids.map(SegmentId::new)));
+
+        let forced = RunOptions {
+            algorithm: Algorithm::Orthogonal,
+            ..Default::default()
+        };
+        let error = run(
+            collect("0 1 4 1\n0 0 3 3\n", InputFormat::Segments, &forced),
+            &forced,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "the orthogonal sweep needs horizontal and vertical segments"
+        );
+
+        // sweeping along another direction allows the vertical segment
+        let directed = RunOptions {
+            direction: Some(1.0),
+            ..options
+        };
+        let text = "0 1 4 1\n2 0 2 3\n1.5 2.5 5 3\n";
+        let found = run(collect(text, InputFormat::Segments, &directed), &directed).unwrap();
+        assert_eq!(found.found, 2);
+        assert_eq!(found.counts.unwrap().counts, [1, 2, 1]);
+        assert_eq!(found.pairs.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_verify_generated() {
+        for distribution in [
+            Distribution::Uniform,
+            Distribution::Clustered,
+            Distribution::Grid,
+            Distribution::Bundles,
+            Distribution::Stars,
+            Distribution::Adversarial,
+        ] {
+            // the numerically hard sets failed at about 2000 segments, every segment of the long ones crosses many others
+            let count = match distribution {
+                Distribution::Bundles | Distribution::Stars => 2000,
+                Distribution::Grid | Distribution::Adversarial => 300,
+                _ => 1000,
+            };
+            for seed in 1..=3 {
+                let lines = generate(&GenerateOptions {
+                    distribution,
+                    count,
+                    seed,
+                    ..Default::default()
+                })
+                .unwrap();
+                let text: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+                let options = RunOptions {
+                    keep_lines: true,
+                    ..Default::default()
+                };
+                let found = run(collect(&text, InputFormat::Segments, &options), &options).unwrap();
+                let expected = found.brute_force().unwrap();
+                let missed = brute_force::missing(&expected, &found.points, 1e-8);
+                let extra = brute_force::missing(&found.points, &expected, 1e-8);
+                assert!(
+                    missed.is_empty() && extra.is_empty(),
+                    "{:?} {}: {} missed, {} extra",
+                    distribution,
+                    seed,
+                    missed.len(),
+                    extra.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_features() {
+        let options = RunOptions {
+            pairs: true,
+            counts: true,
+            ..Default::default()
+        };
+        let wkt = "a LINESTRING (0 0, 2 2, 4 0)\nb LINESTRING (0 1, 4 1)\n";
+        let found = run(collect(wkt, InputFormat::Wkt, &options), &options).unwrap();
+        assert_eq!((found.found, found.segment_count), (2, 3));
+        let pairs = found.pairs.as_ref().unwrap();
+        assert_eq!(pairs[&Point { x: 1.0, y: 1.0 }], [0, 2].map(SegmentId::new));
+        assert_eq!(found.counts.as_ref().unwrap().counts, [1, 1, 2]);
+        assert_eq!(found.brute_force().unwrap(), found.points);
+
+        // a shared edge has no single crossing point
+        let overlap = "a LINESTRING (0 0, 2 0)\nb LINESTRING (1 0, 3 0)\n";
+        let error = run(collect(overlap, InputFormat::Wkt, &options), &options);
+        let Err(RunError::Overlap { segments, features }) = error else {
+            panic!("the paths overlap");
+        };
+        assert_eq!((segments, features), ([0, 1], ["a", "b"].map(String::from)));
+    }
+
+    #[test]
+    fn test_names() {
+        for algorithm in [
+            Algorithm::Sweep,
+            Algorithm::Grid,
+            Algorithm::Brute,
+            Algorithm::Orthogonal,
+            Algorithm::Auto,
+        ] {
+            assert_eq!(algorithm.to_string().parse(), Ok(algorithm));
+        }
+        assert!("rtree".parse::<Algorithm>().is_err());
+    }
+}
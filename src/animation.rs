@@ -0,0 +1,376 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    arena::{Arena, SegmentId},
+    event_queue::Event,
+    geometry::{Bounds, Line, Point},
+    observer::SweepObserver,
+    svg::Canvas,
+    sweep_line::SweepLine,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Begin,
+    End,
+    Intersection,
+}
+
+/// Most segments [`SweepRecorder::write_frames`] draws, as every frame is an SVG of all segments.
+pub const MAX_FRAME_SEGMENTS: usize = 500;
+
+/// Most segments [`SweepRecorder::write_html`] plays back, which stores the segments once and the status of the
+/// sweep line by segment ids for every event.
+pub const MAX_HTML_SEGMENTS: usize = 20_000;
+
+/// State of the sweep right after one event was handled, only the changes to the intersections are kept.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub kind: EventKind,
+    pub point: Point,
+    /// Segments in the sweep line, from top to bottom.
+    pub status: Vec<SegmentId>,
+    /// Intersections discovered while handling this event.
+    pub found: Vec<Point>,
+}
+
+/// Observer recording the history of a sweep, one [`Frame`] per event.
+pub struct SweepRecorder {
+    segments: Vec<Line>,
+    found: Vec<Point>,
+    frames: Vec<Frame>,
+}
+
+impl SweepRecorder {
+    /// The segments of the sweep in input order, so that the ids of the event queue are their positions.
+    pub fn new(segments: Vec<Line>) -> Self {
+        Self {
+            segments,
+            found: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Intersection events waiting in the queue right after the frame `index`, found before and not handled yet.
+    pub fn pending(&self, index: usize) -> Vec<Point> {
+        let mut pending = BTreeSet::new();
+        for frame in &self.frames[..=index] {
+            advance(&mut pending, frame);
+        }
+        pending.into_iter().collect()
+    }
+
+    /// Renders every frame as a standalone SVG document.
+    pub fn render_frames(&self) -> Vec<String> {
+        let Some(bounds) = Bounds::of_lines(&self.segments) else {
+            return Vec::new();
+        };
+        let radius = bounds.extent() * 0.005;
+
+        let mut intersections = Vec::new();
+        let mut pending = BTreeSet::new();
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                advance(&mut pending, frame);
+                let mut canvas = Canvas::new(bounds.clone());
+
+                for line in &self.segments {
+                    canvas.line(line, "#c8c8c8", 1.0);
+                }
+                for &id in &frame.status {
+                    canvas.line(&self.segments[id.index()], "blue", 1.5);
+                }
+                canvas.vertical(frame.point.x, "green", 1.0);
+
+                for point in &intersections {
+                    canvas.circle(point, radius, "#a03030");
+                }
+                for point in &pending {
+                    canvas.circle(point, radius, "orange");
+                }
+                for point in &frame.found {
+                    canvas.circle(point, radius * 2.0, "red");
+                }
+                canvas.circle(&frame.point, radius * 1.5, "green");
+
+                canvas.caption(&format!(
+                    "{}/{} {:?} at {} | status: {} | pending: {}",
+                    index + 1,
+                    self.frames.len(),
+                    frame.kind,
+                    frame.point,
+                    frame.status.len(),
+                    pending.len()
+                ));
+
+                if frame.kind == EventKind::Intersection {
+                    intersections.push(frame.point.clone());
+                }
+
+                canvas.finish()
+            })
+            .collect()
+    }
+
+    /// Writes one SVG per event into `dir`, named `frame_00001.svg` and so on.
+    ///
+    /// See [`MAX_FRAME_SEGMENTS`] for the size of the input this is meant for.
+    pub fn write_frames(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        for (index, svg) in self.render_frames().iter().enumerate() {
+            fs::write(dir.join(format!("frame_{:05}.svg", index + 1)), svg)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a self-contained HTML page which plays back all frames.
+    ///
+    /// The segments are drawn once, every frame only holds the ids of the segments in the sweep line and the
+    /// intersections it found, see [`MAX_HTML_SEGMENTS`].
+    pub fn write_html(&self, path: &Path) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        let Some(bounds) = Bounds::of_lines(&self.segments) else {
+            return write!(file, "{}", HTML_PLAYER.replace("/*DATA*/", "null"));
+        };
+
+        // the sweep line and the markers are added by the player into the group of the static drawing
+        let mut canvas = Canvas::new(bounds.clone());
+        for line in &self.segments {
+            canvas.line(line, "#c8c8c8", 1.0);
+        }
+        let point = |point: &Point| json!([point.x, point.y]);
+        let data = json!({
+            "background": canvas.finish(),
+            "segments": self
+                .segments
+                .iter()
+                .map(|line| json!([line.p.x, line.p.y, line.q.x, line.q.y]))
+                .collect::<Vec<_>>(),
+            "radius": bounds.extent() * 0.005,
+            "low": bounds.min.y - bounds.extent(),
+            "high": bounds.max.y + bounds.extent(),
+            "frames": self
+                .frames
+                .iter()
+                .map(|frame| json!({
+                    "kind": format!("{:?}", frame.kind),
+                    "point": point(&frame.point),
+                    "status": frame.status.iter().map(|id| id.index()).collect::<Vec<_>>(),
+                    "found": frame.found.iter().map(point).collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<Value>>(),
+        });
+
+        // the data ends up in a script element, which must not be closed by a string
+        let data = data.to_string().replace("</", "<\\/");
+        write!(file, "{}", HTML_PLAYER.replace("/*DATA*/", &data))?;
+        file.flush()
+    }
+}
+
+/// Applies the changes of one frame to the pending intersections.
+fn advance(pending: &mut BTreeSet<Point>, frame: &Frame) {
+    if frame.kind == EventKind::Intersection {
+        pending.remove(&frame.point);
+    }
+    pending.extend(frame.found.iter().cloned());
+}
+
+fn event_kind(event: &Event) -> EventKind {
+    match event {
+        Event::Begin { .. } => EventKind::Begin,
+        Event::End { .. } => EventKind::End,
+        Event::Intersection { .. } => EventKind::Intersection,
+    }
+}
+
+impl SweepObserver for SweepRecorder {
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        _segment: SegmentId,
+        _other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+        self.found.push(point.clone());
+    }
+
+    fn event_handled(&mut self, event: &Event, sweep_line: &SweepLine, _segments: &Arena) {
+        self.frames.push(Frame {
+            kind: event_kind(event),
+            point: event.point().clone(),
+            status: sweep_line.elements.iter().map(|e| e.segment).collect(),
+            found: std::mem::take(&mut self.found),
+        });
+    }
+}
+
+const HTML_PLAYER: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Sweep line</title>
+<style>
+  body { font-family: sans-serif; }
+  #frame svg { max-width: 100%; height: auto; border: 1px solid #ddd; }
+  #position { width: 600px; }
+</style>
+</head>
+<body>
+<div>
+  <button id="prev">&lt;</button>
+  <button id="play">play</button>
+  <button id="next">&gt;</button>
+  <input id="position" type="range" min="0" value="0">
+  <span id="label"></span>
+</div>
+<div id="frame"></div>
+<script>
+const data = /*DATA*/;
+const frames = data ? data.frames : [];
+let current = 0;
+let timer = null;
+const position = document.getElementById("position");
+position.max = Math.max(frames.length - 1, 0);
+const svg = "http://www.w3.org/2000/svg";
+let overlay = null;
+if (data) {
+  document.getElementById("frame").innerHTML = data.background;
+  overlay = document.createElementNS(svg, "g");
+  document.querySelector("#frame svg > g").appendChild(overlay);
+}
+
+function line(x1, y1, x2, y2, color, width) {
+  const element = document.createElementNS(svg, "line");
+  for (const [name, value] of Object.entries({x1, y1, x2, y2, stroke: color, "stroke-width": width})) {
+    element.setAttribute(name, value);
+  }
+  element.setAttribute("vector-effect", "non-scaling-stroke");
+  overlay.appendChild(element);
+}
+
+function circle(point, radius, color) {
+  const element = document.createElementNS(svg, "circle");
+  element.setAttribute("cx", point[0]);
+  element.setAttribute("cy", point[1]);
+  element.setAttribute("r", radius);
+  element.setAttribute("fill", color);
+  overlay.appendChild(element);
+}
+
+function show(index) {
+  current = Math.min(Math.max(index, 0), frames.length - 1);
+  position.value = current;
+  // replay the intersections up to the frame, only their changes are stored
+  const handled = [];
+  const pending = new Map();
+  for (const frame of frames.slice(0, current + 1)) {
+    if (frame.kind === "Intersection") {
+      pending.delete(frame.point.join(" "));
+    }
+    for (const point of frame.found) {
+      pending.set(point.join(" "), point);
+    }
+  }
+  for (const frame of frames.slice(0, current)) {
+    if (frame.kind === "Intersection") handled.push(frame.point);
+  }
+  const frame = frames[current];
+  overlay.replaceChildren();
+  for (const id of frame.status) {
+    line(...data.segments[id], "blue", 1.5);
+  }
+  line(frame.point[0], data.low, frame.point[0], data.high, "green", 1);
+  handled.forEach((point) => circle(point, data.radius, "#a03030"));
+  pending.forEach((point) => circle(point, data.radius, "orange"));
+  frame.found.forEach((point) => circle(point, data.radius * 2, "red"));
+  circle(frame.point, data.radius * 1.5, "green");
+  document.getElementById("label").textContent = (current + 1) + " / " + frames.length + " " + frame.kind
+    + " at " + frame.point.join(" ") + " | status: " + frame.status.length + " | pending: " + pending.size;
+}
+
+function toggle() {
+  if (timer) {
+    clearInterval(timer);
+    timer = null;
+    document.getElementById("play").textContent = "play";
+    return;
+  }
+  document.getElementById("play").textContent = "pause";
+  timer = setInterval(() => {
+    if (current + 1 >= frames.length) { toggle(); return; }
+    show(current + 1);
+  }, 300);
+}
+
+document.getElementById("prev").onclick = () => show(current - 1);
+document.getElementById("next").onclick = () => show(current + 1);
+document.getElementById("play").onclick = toggle;
+position.oninput = () => show(parseInt(position.value));
+document.onkeydown = (e) => {
+  if (e.key === "ArrowLeft") show(current - 1);
+  if (e.key === "ArrowRight") show(current + 1);
+  if (e.key === " ") { e.preventDefault(); toggle(); }
+};
+if (frames.length > 0) show(0);
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::event_queue::EventQueue;
+
+    #[test]
+    fn test_record_frames() {
+        let lines = vec![
+            Line::from_str("0 1 5 1").unwrap(),
+            Line::from_str("1 0 4 2").unwrap(),
+        ];
+
+        let mut recorder = SweepRecorder::new(lines.clone());
+        EventQueue::new(lines).sweep_with_observer(&mut recorder);
+
+        let frames = recorder.frames();
+        assert_eq!(frames.len(), 5);
+
+        assert_eq!(frames[1].kind, EventKind::Begin);
+        assert_eq!(frames[1].status.len(), 2);
+        assert_eq!(recorder.pending(1), vec![Point { x: 2.5, y: 1.0 }]);
+        assert_eq!(frames[1].found, vec![Point { x: 2.5, y: 1.0 }]);
+
+        assert_eq!(frames[2].kind, EventKind::Intersection);
+        assert!(recorder.pending(2).is_empty());
+        assert!(frames[2].found.is_empty());
+
+        assert_eq!(frames[4].kind, EventKind::End);
+        assert!(frames[4].status.is_empty());
+
+        assert_eq!(recorder.render_frames().len(), 5);
+
+        // the player holds every segment once and the status by id
+        let path = std::env::temp_dir().join(format!("sweep-player-{}.html", std::process::id()));
+        recorder.write_html(&path).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(html.matches("<line").count(), 2);
+        assert!(html.contains(r#""status":[1,0]"#) || html.contains(r#""status":[0,1]"#));
+    }
+}
@@ -0,0 +1,579 @@
+//! Circular arcs next to segments, swept as x-monotone pieces.
+//!
+//! Every arc is split at the leftmost and rightmost points of its circle, so each piece lies on the upper or the
+//! lower half of the circle and has a single y for every x, like a segment. The sweep orders the pieces by evaluating
+//! y at the sweep x, and intersections of segments and arcs are computed from the circle equations instead of
+//! approximating the arcs by many short segments.
+
+use std::{
+    collections::BTreeSet,
+    f64::consts::{PI, TAU},
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::geometry::{column, parse_values, Bounds, Line, ParseLineError, Point};
+
+/// Angles closer than this in radians are the same, so points computed on the end of a piece still lie on it.
+const ANGLE_TOLERANCE: f64 = 1e-9;
+
+/// Circular arc from the `start` to the `end` angle counterclockwise, in degrees from the x axis.
+///
+/// Equal angles give the full circle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arc {
+    pub center: Point,
+    pub radius: f64,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Arc {
+    /// The x-monotone pieces of the arc, from the start to the end angle.
+    pub fn pieces(&self) -> Vec<ArcPiece> {
+        let start = self.start.to_radians().rem_euclid(TAU);
+        let sweep = (self.end - self.start).rem_euclid(360.0);
+        let sweep = if sweep == 0.0 {
+            TAU
+        } else {
+            sweep.to_radians()
+        };
+        let end = start + sweep;
+
+        let mut pieces = Vec::new();
+        let mut from = start;
+        while end - from > ANGLE_TOLERANCE {
+            // split at every multiple of pi, the leftmost and rightmost points of the circle
+            let half = (from / PI + ANGLE_TOLERANCE).floor();
+            let to = end.min((half + 1.0) * PI);
+            let turns = (half / 2.0).floor() * TAU;
+            pieces.push(ArcPiece {
+                center: self.center.clone(),
+                radius: self.radius,
+                from: from - turns,
+                to: to - turns,
+            });
+            from = to;
+        }
+        pieces
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let pieces = self.pieces();
+        let ends: Vec<_> = pieces
+            .iter()
+            .flat_map(|piece| [piece.left(), piece.right(), piece.extreme()])
+            .collect();
+        Bounds::of_points(&ends).expect("an arc has at least one piece")
+    }
+}
+
+impl Display for Arc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.center, self.radius, self.start, self.end
+        )
+    }
+}
+
+impl FromStr for Arc {
+    type Err = ParseLineError;
+
+    /// Parses `cx cy r start end`, the inverse of the `Display` implementation.
+    ///
+    /// All values must be finite and the radius positive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = parse_values(s, |found, column| ParseLineError::NotFiveElements {
+            found,
+            column,
+        })?;
+        let invalid = |index: usize, reason| {
+            let value = s
+                .split_whitespace()
+                .nth(index)
+                .expect("five values were parsed");
+            ParseLineError::InvalidValue {
+                column: column(s, value),
+                reason,
+            }
+        };
+        let [x, y, radius, start, end] = values;
+        if radius <= 0.0 {
+            return Err(invalid(2, "the radius of an arc must be positive"));
+        }
+
+        Ok(Arc {
+            center: Point { x, y },
+            radius,
+            start,
+            end,
+        })
+    }
+}
+
+/// Part of an arc on the upper (`0 <= from < to <= pi`) or lower (`pi <= from < to <= 2 pi`) half of its circle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcPiece {
+    pub center: Point,
+    pub radius: f64,
+    /// Angles in radians
+    pub from: f64,
+    pub to: f64,
+}
+
+impl ArcPiece {
+    fn is_upper(&self) -> bool {
+        self.from + self.to < TAU
+    }
+
+    fn at(&self, angle: f64) -> Point {
+        // the pieces are joined at multiples of pi, which must give the same point for both pieces
+        let halves = (angle / PI).round();
+        if (angle - halves * PI).abs() < ANGLE_TOLERANCE {
+            let side = if halves % 2.0 == 0.0 { 1.0 } else { -1.0 };
+            return Point {
+                x: self.center.x + side * self.radius,
+                y: self.center.y,
+            };
+        }
+        Point {
+            x: self.center.x + self.radius * angle.cos(),
+            y: self.center.y + self.radius * angle.sin(),
+        }
+    }
+
+    pub fn left(&self) -> Point {
+        if self.is_upper() {
+            self.at(self.to)
+        } else {
+            self.at(self.from)
+        }
+    }
+
+    pub fn right(&self) -> Point {
+        if self.is_upper() {
+            self.at(self.from)
+        } else {
+            self.at(self.to)
+        }
+    }
+
+    /// The highest point of an upper piece or the lowest of a lower one.
+    fn extreme(&self) -> Point {
+        let top = if self.is_upper() { PI / 2.0 } else { 1.5 * PI };
+        self.at(top.clamp(self.from, self.to))
+    }
+
+    /// y of the piece at `x`, which is clamped to the x range of the piece.
+    pub fn y(&self, x: f64) -> f64 {
+        let dx = x.clamp(self.left().x, self.right().x) - self.center.x;
+        let dy = (self.radius * self.radius - dx * dx).max(0.0).sqrt();
+        if self.is_upper() {
+            self.center.y + dy
+        } else {
+            self.center.y - dy
+        }
+    }
+
+    /// Whether the point on the circle lies on the piece.
+    fn contains(&self, point: &Point) -> bool {
+        let angle = (point.y - self.center.y).atan2(point.x - self.center.x);
+        [angle - TAU, angle, angle + TAU].iter().any(|&angle| {
+            self.from - ANGLE_TOLERANCE <= angle && angle <= self.to + ANGLE_TOLERANCE
+        })
+    }
+}
+
+/// An x-monotone curve of the sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Curve {
+    Segment(Line),
+    Arc(ArcPiece),
+}
+
+impl Curve {
+    pub fn left(&self) -> Point {
+        match self {
+            Curve::Segment(line) => line.p.clone().min(line.q.clone()),
+            Curve::Arc(piece) => piece.left(),
+        }
+    }
+
+    pub fn right(&self) -> Point {
+        match self {
+            Curve::Segment(line) => line.p.clone().max(line.q.clone()),
+            Curve::Arc(piece) => piece.right(),
+        }
+    }
+
+    pub fn y(&self, x: f64) -> f64 {
+        match self {
+            Curve::Segment(line) => line.y(x),
+            Curve::Arc(piece) => piece.y(x),
+        }
+    }
+
+    /// Intersections of both curves, at most two.
+    pub fn intersections(&self, other: &Curve) -> Vec<Point> {
+        match (self, other) {
+            (Curve::Segment(line), Curve::Segment(other_line)) => {
+                line.intersection(other_line).into_iter().collect()
+            }
+            (Curve::Segment(line), Curve::Arc(piece))
+            | (Curve::Arc(piece), Curve::Segment(line)) => {
+                line_circle(line, &piece.center, piece.radius)
+                    .into_iter()
+                    .filter(|point| piece.contains(point))
+                    .collect()
+            }
+            (Curve::Arc(piece), Curve::Arc(other_piece)) => circle_circle(piece, other_piece)
+                .into_iter()
+                .filter(|point| piece.contains(point) && other_piece.contains(point))
+                .collect(),
+        }
+    }
+}
+
+/// Points of the segment on the circle.
+fn line_circle(line: &Line, center: &Point, radius: f64) -> Vec<Point> {
+    let (dx, dy) = (line.q.x - line.p.x, line.q.y - line.p.y);
+    let (fx, fy) = (line.p.x - center.x, line.p.y - center.y);
+    let a = dx * dx + dy * dy;
+    let b = 2.0 * (fx * dx + fy * dy);
+    let c = fx * fx + fy * fy - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return Vec::new();
+    }
+
+    let root = discriminant.sqrt();
+    let mut ts = vec![(-b - root) / (2.0 * a)];
+    if root > 0.0 {
+        ts.push((-b + root) / (2.0 * a));
+    }
+    ts.into_iter()
+        .filter(|t| (0.0..=1.0).contains(t))
+        .map(|t| Point {
+            x: line.p.x + t * dx,
+            y: line.p.y + t * dy,
+        })
+        .collect()
+}
+
+/// Intersections of the circles of both pieces, none for circles with the same center.
+fn circle_circle(piece: &ArcPiece, other: &ArcPiece) -> Vec<Point> {
+    // like for segments, compute the points independent of the order of both circles
+    let by_circle = |piece: &ArcPiece| (piece.center.clone(), piece.radius.to_bits());
+    let (piece, other) = if by_circle(piece) <= by_circle(other) {
+        (piece, other)
+    } else {
+        (other, piece)
+    };
+    let (dx, dy) = (
+        other.center.x - piece.center.x,
+        other.center.y - piece.center.y,
+    );
+    let distance = dx.hypot(dy);
+    if distance == 0.0
+        || distance > piece.radius + other.radius
+        || distance < (piece.radius - other.radius).abs()
+    {
+        return Vec::new();
+    }
+
+    // distance from the first center to the chord through both intersections, and half the chord
+    let a = (piece.radius * piece.radius - other.radius * other.radius + distance * distance)
+        / (2.0 * distance);
+    let h = (piece.radius * piece.radius - a * a).max(0.0).sqrt();
+    let (mx, my) = (
+        piece.center.x + a * dx / distance,
+        piece.center.y + a * dy / distance,
+    );
+    let mut points = vec![Point {
+        x: mx - h * dy / distance,
+        y: my + h * dx / distance,
+    }];
+    if h > 0.0 {
+        points.push(Point {
+            x: mx + h * dy / distance,
+            y: my - h * dx / distance,
+        });
+    }
+    points
+}
+
+/// The segments and the pieces of the arcs, each with the index of its segment or arc.
+///
+/// Segments come first, so the index of an arc is counted after all segments.
+fn curves(lines: &[Line], arcs: &[Arc]) -> Vec<(usize, Curve)> {
+    let segments = lines.iter().cloned().map(Curve::Segment);
+    let pieces = arcs
+        .iter()
+        .map(Arc::pieces)
+        .map(|pieces| pieces.into_iter().map(Curve::Arc).collect::<Vec<_>>());
+    segments
+        .map(|curve| vec![curve])
+        .chain(pieces)
+        .enumerate()
+        .flat_map(|(source, curves)| curves.into_iter().map(move |curve| (source, curve)))
+        .collect()
+}
+
+/// Tests every pair of curves in O(n²), the reference to check [`intersections`] against.
+pub fn brute_force(lines: &[Line], arcs: &[Arc]) -> BTreeSet<Point> {
+    let curves = curves(lines, arcs);
+    let mut intersections = BTreeSet::new();
+    for (index, (source, curve)) in curves.iter().enumerate() {
+        for (other_source, other_curve) in &curves[index + 1..] {
+            if source == other_source {
+                continue;
+            }
+            for point in curve.intersections(other_curve) {
+                intersections.insert(point.round(9));
+            }
+        }
+    }
+    intersections
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum EventKind {
+    Begin(usize),
+    Intersection(usize, usize),
+    End(usize),
+}
+
+/// Intersections of the segments and arcs, rounded to 9 decimals like in the general sweep.
+///
+/// The pieces of an arc only meet at their ends and are not intersected with each other. Like for
+/// [`EventQueue::sweep`](crate::event_queue::EventQueue::sweep), segments must not be vertical.
+pub fn intersections(lines: &[Line], arcs: &[Arc]) -> BTreeSet<Point> {
+    let (sources, curves): (Vec<_>, Vec<_>) = curves(lines, arcs).into_iter().unzip();
+    let intersect = |a: usize, b: usize| match sources[a] == sources[b] {
+        true => Vec::new(),
+        false => curves[a].intersections(&curves[b]),
+    };
+    crossings(&curves, intersect)
+        .into_iter()
+        .map(|(point, _, _)| point)
+        .collect()
+}
+
+/// Every pair of intersecting curves by their indices, smaller index first, with the intersection rounded to 9 decimals.
+///
+/// Unlike the general sweep, curves may share end points, which are reported like other intersections. The curves
+/// `a < b` are intersected by `intersect(a, b)`, usually [`Curve::intersections`], which may leave out pairs which
+/// should not be reported. Panics for vertical segments.
+pub fn crossings(
+    curves: &[Curve],
+    intersect: impl Fn(usize, usize) -> Vec<Point>,
+) -> BTreeSet<(Point, usize, usize)> {
+    if let Some(Curve::Segment(line)) = curves
+        .iter()
+        .find(|curve| matches!(curve, Curve::Segment(line) if line.p.x == line.q.x))
+    {
+        panic!("vertical segment {}", line);
+    }
+    let mut events: BTreeSet<_> = curves
+        .iter()
+        .enumerate()
+        .flat_map(|(id, curve)| {
+            [
+                (curve.left(), EventKind::Begin(id)),
+                (curve.right(), EventKind::End(id)),
+            ]
+        })
+        .collect();
+
+    // curves crossing the sweep line, from top to bottom
+    let mut status: Vec<usize> = Vec::new();
+    let mut crossings = BTreeSet::new();
+    while let Some((point, kind)) = events.pop_first() {
+        // like in the sweep line, order a bit to the right of the event so curves meeting in it are sorted
+        let x = point.x + 1e-9;
+        let sort = |status: &mut Vec<usize>| {
+            status.sort_by(|&a, &b| curves[b].y(x).total_cmp(&curves[a].y(x)));
+        };
+        // end points are not rounded, an intersection with them may round to a point just before, and
+        // intersections just after them may round to a point before the rounded end point
+        let behind = point.clone().min(point.round(9));
+        let mut check = |a: usize, b: usize, events: &mut BTreeSet<_>| {
+            let (a, b) = (a.min(b), a.max(b));
+            for found in intersect(a, b) {
+                let found = found.round(9);
+                if found < behind || !crossings.insert((found.clone(), a, b)) {
+                    continue;
+                }
+                if found != point {
+                    events.insert((found, EventKind::Intersection(a, b)));
+                }
+            }
+        };
+        let through =
+            |id: usize| (curves[id].y(point.x) - point.y).abs() <= 1e-6 * (1.0 + point.y.abs());
+        // checks the neighbors of the curves through the event point next to `index`, and all pairs of them,
+        // as curves meeting in the point may be separated by others meeting there as well
+        let mut check_around = |status: &Vec<usize>, index: usize, events: &mut BTreeSet<_>| {
+            let mut from = index;
+            while from > 0 && through(status[from - 1]) {
+                from -= 1;
+            }
+            let mut to = index;
+            while to + 1 < status.len() && through(status[to + 1]) {
+                to += 1;
+            }
+            for i in from..=to {
+                for j in i + 1..=to {
+                    check(status[i], status[j], events);
+                }
+            }
+            if from > 0 {
+                check(status[from - 1], status[from], events);
+            }
+            if to + 1 < status.len() {
+                check(status[to], status[to + 1], events);
+            }
+        };
+
+        match kind {
+            EventKind::Begin(id) => {
+                status.push(id);
+                sort(&mut status);
+                let index = status.iter().position(|&other| other == id).unwrap();
+                check_around(&status, index, &mut events);
+            }
+            EventKind::End(id) => {
+                let index = status.iter().position(|&other| other == id).unwrap();
+                check_around(&status, index, &mut events);
+                status.remove(index);
+                if index > 0 && index < status.len() {
+                    check_around(&status, index - 1, &mut events);
+                }
+            }
+            EventKind::Intersection(a, b) => {
+                sort(&mut status);
+                // the rounded point may lie just behind the end of a curve, which then already left the status
+                for id in [a, b] {
+                    if let Some(index) = status.iter().position(|&other| other == id) {
+                        check_around(&status, index, &mut events);
+                    }
+                }
+            }
+        }
+    }
+    crossings
+}
+
+#[cfg(test)]
+mod test {
+    use std::slice;
+
+    use super::*;
+    use crate::generate::Rng;
+
+    fn arc(s: &str) -> Arc {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pieces() {
+        let pieces = arc("0 0 1 -90 270").pieces();
+        assert_eq!(pieces.len(), 3);
+        assert!(!pieces[0].is_upper() && pieces[1].is_upper() && !pieces[2].is_upper());
+        assert!((pieces[2].to - 1.5 * PI).abs() < 1e-12);
+        // the pieces of the circle are joined at its leftmost and rightmost points
+        assert_eq!(pieces[0].right(), pieces[1].right());
+        assert_eq!(pieces[1].left(), pieces[2].left());
+
+        assert_eq!(arc("0 0 2 10 80").pieces().len(), 1);
+        let bounds = arc("1 1 2 0 180").bounds();
+        assert_eq!((bounds.min.y, bounds.max.y), (1.0, 3.0));
+        assert_eq!(
+            arc("1 1 2 0 180").to_string().parse(),
+            Ok(arc("1 1 2 0 180"))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let error = |s: &str| s.parse::<Arc>().unwrap_err();
+        assert_eq!(
+            error("0 0 -1 0 180"),
+            ParseLineError::InvalidValue {
+                column: 5,
+                reason: "the radius of an arc must be positive"
+            }
+        );
+        assert_eq!(error("0 0 0 0 180").column(), 5);
+        assert_eq!(error("0 0 NaN 0 180").column(), 5);
+        assert_eq!(error("0 0 1 0 inf").column(), 9);
+        assert_eq!(error(" 0  -inf 1 0 90").column(), 5);
+        assert_eq!(arc("0 0 1e-3 -720 720").pieces().len(), 2);
+    }
+
+    #[test]
+    fn test_curve_intersections() {
+        let circle = arc("0 0 1 0 0");
+        let horizontal: Line = "-2 0.5 2 0.5".parse().unwrap();
+        let found = brute_force(slice::from_ref(&horizontal), slice::from_ref(&circle));
+        let expected: BTreeSet<_> = [(-0.75f64.sqrt(), 0.5), (0.75f64.sqrt(), 0.5)]
+            .into_iter()
+            .map(|(x, y)| Point { x, y }.round(9))
+            .collect();
+        assert_eq!(found, expected);
+
+        // the upper half only
+        let upper = arc("0 0 1 0 180");
+        let below: Line = "-2 -0.5 2 -0.5".parse().unwrap();
+        assert!(brute_force(&[below], &[upper]).is_empty());
+
+        // circles of radius 1 around (0, 0) and (1, 0) meet at x = 0.5
+        let other = arc("1 0 1 0 0");
+        let found = brute_force(&[], &[circle, other]);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|point| point.x == 0.5));
+        assert_eq!(
+            intersections(&[horizontal], &[arc("0 0 1 0 0"), arc("1 0 1 0 0")]).len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_same_as_brute_force() {
+        let mut rng = Rng::new(11);
+        let lines: Vec<_> = (0..150)
+            .map(|_| {
+                let (x, y) = (rng.range(0.0, 100.0), rng.range(0.0, 100.0));
+                Line {
+                    p: Point { x, y },
+                    q: Point {
+                        x: x + rng.range(-20.0, 20.0),
+                        y: y + rng.range(-20.0, 20.0),
+                    },
+                }
+            })
+            .collect();
+        let arcs: Vec<_> = (0..150)
+            .map(|_| Arc {
+                center: Point {
+                    x: rng.range(0.0, 100.0),
+                    y: rng.range(0.0, 100.0),
+                },
+                radius: rng.range(1.0, 15.0),
+                start: rng.range(0.0, 360.0),
+                end: rng.range(0.0, 360.0),
+            })
+            .collect();
+
+        let expected = brute_force(&lines, &arcs);
+        assert!(expected.len() > 300);
+        assert_eq!(intersections(&lines, &arcs), expected);
+        // without arcs, the same as the general sweep
+        assert_eq!(
+            intersections(&lines, &[]),
+            crate::event_queue::EventQueue::new(lines).sweep()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Segments of a sweep, stored once and referred to by a [`SegmentId`] in the event queue and the sweep line.
+
+use std::{
+    fmt::{self, Display},
+    ops::Index,
+};
+
+use crate::geometry::Line;
+
+/// Position of a segment in its [`Arena`], which identifies the segment exactly, unlike comparing coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SegmentId(u32);
+
+impl SegmentId {
+    /// The id of the segment at `index` of a slice, the same id an arena of these segments hands out.
+    ///
+    /// Panics if the index does not fit into an id.
+    pub fn new(index: usize) -> Self {
+        SegmentId(u32::try_from(index).expect("too many segments for a SegmentId"))
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Display for SegmentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Owns the segments of a sweep.
+#[derive(Debug, Clone, Default)]
+pub struct Arena {
+    lines: Vec<Line>,
+}
+
+impl Arena {
+    /// Stores the segment, ids are handed out in the order the segments are pushed.
+    ///
+    /// Panics if there are more than `u32::MAX` segments.
+    pub fn push(&mut self, line: Line) -> SegmentId {
+        let id = SegmentId::new(self.lines.len());
+        self.lines.push(line);
+        id
+    }
+
+    /// Removes the segment pushed last, its id may be handed out again.
+    pub fn pop(&mut self) -> Option<Line> {
+        self.lines.pop()
+    }
+
+    /// Stores another segment under an id handed out before and returns the old segment.
+    ///
+    /// Panics if the id was not handed out by this arena.
+    pub fn replace(&mut self, id: SegmentId, line: Line) -> Line {
+        std::mem::replace(&mut self.lines[id.index()], line)
+    }
+
+    pub fn get(&self, id: SegmentId) -> Option<&Line> {
+        self.lines.get(id.index())
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SegmentId, &Line)> {
+        (0..).map(SegmentId).zip(&self.lines)
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+}
+
+impl Index<SegmentId> for Arena {
+    type Output = Line;
+
+    fn index(&self, id: SegmentId) -> &Line {
+        &self.lines[id.index()]
+    }
+}
+
+impl FromIterator<Line> for Arena {
+    fn from_iter<I: IntoIterator<Item = Line>>(lines: I) -> Self {
+        let mut arena = Arena::default();
+        for line in lines {
+            arena.push(line);
+        }
+        arena
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_ids() {
+        let line = Line::from_str("0 0 1 1").unwrap();
+        let mut arena = Arena::default();
+        let a = arena.push(line.clone());
+        // the same coordinates are still a different segment
+        let b = arena.push(line.clone());
+
+        assert_ne!(a, b);
+        assert_eq!(arena[a], line);
+        assert_eq!(arena.get(b), Some(&line));
+        assert_eq!(arena.len(), 2);
+        assert_eq!(b.index(), 1);
+        assert_eq!(SegmentId::new(1), b);
+        assert_eq!(arena.iter().map(|(id, _)| id).collect::<Vec<_>>(), [a, b]);
+
+        let other = Line::from_str("0 1 1 0").unwrap();
+        assert_eq!(arena.replace(a, other.clone()), line);
+        assert_eq!(arena[a], other);
+        assert_eq!(arena.len(), 2);
+    }
+}
@@ -0,0 +1,313 @@
+//! Compact little-endian binary format for segments and intersections.
+//!
+//! Every file starts with a 48 byte header:
+//!
+//! | Offset | Type       | Content                                        |
+//! | ------ | ---------- | ---------------------------------------------- |
+//! | 0      | `[u8; 4]`  | magic, `SWLS` for segments, `SWLI` for points  |
+//! | 4      | `u32`      | version, currently 1                           |
+//! | 8      | `u64`      | number of records                              |
+//! | 16     | `[f64; 4]` | bounding box `min_x min_y max_x max_y`, 0 if empty |
+//!
+//! followed by the records, `x1 y1 x2 y2` as `f64` per segment and `x y` per intersection.
+
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+use crate::geometry::{Bounds, Line, Point};
+
+pub const SEGMENTS_MAGIC: [u8; 4] = *b"SWLS";
+pub const INTERSECTIONS_MAGIC: [u8; 4] = *b"SWLI";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub version: u32,
+    pub count: u64,
+    pub bounds: Option<Bounds>,
+}
+
+impl Header {
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.magic)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&self.count.to_le_bytes())?;
+
+        let bounds = match &self.bounds {
+            Some(b) => [b.min.x, b.min.y, b.max.x, b.max.y],
+            None => [0.0; 4],
+        };
+        for value in bounds {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads the header, which is only accepted if it has one of the known magic numbers and versions.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != SEGMENTS_MAGIC && magic != INTERSECTIONS_MAGIC {
+            return Err(invalid_data(format!("unknown magic number {:?}", magic)));
+        }
+
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported version {}", version)));
+        }
+
+        let mut count = [0; 8];
+        reader.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count);
+
+        let [min_x, min_y, max_x, max_y] = read_values(reader)?;
+        let bounds = (count > 0).then_some(Bounds {
+            min: Point { x: min_x, y: min_y },
+            max: Point { x: max_x, y: max_y },
+        });
+
+        Ok(Header {
+            magic,
+            version,
+            count,
+            bounds,
+        })
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_values<R: Read, const N: usize>(reader: &mut R) -> io::Result<[f64; N]> {
+    let mut values = [0.0; N];
+    let mut bytes = [0; 8];
+    for value in values.iter_mut() {
+        reader.read_exact(&mut bytes)?;
+        *value = f64::from_le_bytes(bytes);
+    }
+    Ok(values)
+}
+
+fn write_values<W: Write>(writer: &mut W, values: &[f64]) -> io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Returns true if the data starts with the magic number of a binary file.
+pub fn is_binary(start: &[u8]) -> bool {
+    start.starts_with(&SEGMENTS_MAGIC) || start.starts_with(&INTERSECTIONS_MAGIC)
+}
+
+pub fn write_segments<W: Write>(writer: W, lines: &[Line]) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    Header {
+        magic: SEGMENTS_MAGIC,
+        version: VERSION,
+        count: lines.len() as u64,
+        bounds: Bounds::of_lines(lines),
+    }
+    .write(&mut writer)?;
+
+    for line in lines {
+        write_values(&mut writer, &[line.p.x, line.p.y, line.q.x, line.q.y])?;
+    }
+    writer.flush()
+}
+
+pub fn write_intersections<'a, W: Write, I>(writer: W, points: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = &'a Point>,
+    I::IntoIter: ExactSizeIterator + Clone,
+{
+    let points = points.into_iter();
+    let mut writer = BufWriter::new(writer);
+    Header {
+        magic: INTERSECTIONS_MAGIC,
+        version: VERSION,
+        count: points.len() as u64,
+        bounds: Bounds::of_points(points.clone()),
+    }
+    .write(&mut writer)?;
+
+    for point in points {
+        write_values(&mut writer, &[point.x, point.y])?;
+    }
+    writer.flush()
+}
+
+/// Writes the records one by one as they are produced, and the header with their number and bounding box when
+/// finished, which needs a writer that can seek back to the start.
+pub struct StreamWriter<W: Write + Seek> {
+    writer: BufWriter<W>,
+    header: Header,
+}
+
+impl<W: Write + Seek> StreamWriter<W> {
+    /// A writer for segments, only [`StreamWriter::write_segment`] may be called.
+    pub fn segments(writer: W) -> io::Result<Self> {
+        Self::new(writer, SEGMENTS_MAGIC)
+    }
+
+    /// A writer for intersections, only [`StreamWriter::write_point`] may be called.
+    pub fn intersections(writer: W) -> io::Result<Self> {
+        Self::new(writer, INTERSECTIONS_MAGIC)
+    }
+
+    fn new(writer: W, magic: [u8; 4]) -> io::Result<Self> {
+        let mut writer = BufWriter::new(writer);
+        let header = Header {
+            magic,
+            version: VERSION,
+            count: 0,
+            bounds: None,
+        };
+        // a placeholder until the records are counted
+        header.write(&mut writer)?;
+        Ok(StreamWriter { writer, header })
+    }
+
+    fn extend(&mut self, point: &Point) {
+        match &mut self.header.bounds {
+            Some(bounds) => bounds.extend(point),
+            None => {
+                self.header.bounds = Some(Bounds {
+                    min: point.clone(),
+                    max: point.clone(),
+                })
+            }
+        }
+    }
+
+    pub fn write_segment(&mut self, line: &Line) -> io::Result<()> {
+        debug_assert_eq!(self.header.magic, SEGMENTS_MAGIC);
+        self.extend(&line.p);
+        self.extend(&line.q);
+        self.header.count += 1;
+        write_values(&mut self.writer, &[line.p.x, line.p.y, line.q.x, line.q.y])
+    }
+
+    pub fn write_point(&mut self, point: &Point) -> io::Result<()> {
+        debug_assert_eq!(self.header.magic, INTERSECTIONS_MAGIC);
+        self.extend(point);
+        self.header.count += 1;
+        write_values(&mut self.writer, &[point.x, point.y])
+    }
+
+    /// Writes the final header and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.header.write(&mut self.writer)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.into_inner().map_err(|err| err.into_error())
+    }
+}
+
+/// Hands every segment to `f` while reading, like [`SegmentReader::read`](crate::input::SegmentReader::read).
+pub fn read_segments<R: Read>(reader: R, mut f: impl FnMut(Line)) -> io::Result<Header> {
+    let mut reader = BufReader::new(reader);
+    let header = Header::read(&mut reader)?;
+    if header.magic != SEGMENTS_MAGIC {
+        return Err(invalid_data("not a segment file".to_string()));
+    }
+
+    for _ in 0..header.count {
+        let [x1, y1, x2, y2] = read_values(&mut reader)?;
+        f(Line {
+            p: Point { x: x1, y: y1 },
+            q: Point { x: x2, y: y2 },
+        });
+    }
+    Ok(header)
+}
+
+pub fn read_intersections<R: Read>(reader: R) -> io::Result<Vec<Point>> {
+    let mut reader = BufReader::new(reader);
+    let header = Header::read(&mut reader)?;
+    if header.magic != INTERSECTIONS_MAGIC {
+        return Err(invalid_data("not an intersection file".to_string()));
+    }
+
+    (0..header.count)
+        .map(|_| read_values(&mut reader).map(|[x, y]| Point { x, y }))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_segments_round_trip() {
+        let lines = vec![
+            Line::from_str("0 1 5 1").unwrap(),
+            Line::from_str("1 0.1 4 -2").unwrap(),
+        ];
+        let mut data = Vec::new();
+        write_segments(&mut data, &lines).unwrap();
+        assert_eq!(data.len(), 48 + 2 * 32);
+        assert!(is_binary(&data));
+
+        let mut read = Vec::new();
+        let header = read_segments(data.as_slice(), |line| read.push(line)).unwrap();
+        assert_eq!(read, lines);
+        assert_eq!(header.count, 2);
+        assert_eq!(header.bounds, Bounds::of_lines(&lines));
+    }
+
+    #[test]
+    fn test_intersections_round_trip() {
+        let points = [Point {
+            x: 0.1 + 0.2,
+            y: 1.0 / 3.0,
+        }];
+        let mut data = Vec::new();
+        write_intersections(&mut data, &points).unwrap();
+
+        assert_eq!(read_intersections(data.as_slice()).unwrap(), points);
+        assert!(read_segments(data.as_slice(), |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_stream_writer() {
+        let lines = vec![
+            Line::from_str("0 1 5 1").unwrap(),
+            Line::from_str("1 0.1 4 -2").unwrap(),
+        ];
+        let mut writer = StreamWriter::segments(io::Cursor::new(Vec::new())).unwrap();
+        for line in &lines {
+            writer.write_segment(line).unwrap();
+        }
+        let data = writer.finish().unwrap().into_inner();
+
+        let mut expected = Vec::new();
+        write_segments(&mut expected, &lines).unwrap();
+        assert_eq!(data, expected);
+
+        let writer = StreamWriter::intersections(io::Cursor::new(Vec::new())).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+        assert!(read_intersections(data.as_slice()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(Header::read(&mut "SWLX".as_bytes()).is_err());
+
+        let mut data = Vec::new();
+        write_segments(&mut data, &[]).unwrap();
+        data[4] = 2;
+        assert!(read_segments(data.as_slice(), |_| {}).is_err());
+
+        // truncated record
+        let mut data = Vec::new();
+        write_segments(&mut data, &[Line::from_str("0 1 5 1").unwrap()]).unwrap();
+        data.pop();
+        assert!(read_segments(data.as_slice(), |_| {}).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    arena::SegmentId,
+    geometry::{Line, Point},
+};
+
+/// Tests every pair of segments in O(n²), the reference to check the sweep against.
+///
+/// Intersections are rounded to 9 decimals like in the sweep, so both results can be compared directly.
+pub fn intersections(lines: &[Line]) -> BTreeSet<Point> {
+    let mut intersections = BTreeSet::new();
+    pairs(lines, |point, _, _| {
+        intersections.insert(point);
+    });
+    intersections
+}
+
+/// Like [`intersections`], with the ids of all segments through each point in ascending order, which are their
+/// positions in `lines`.
+pub fn crossings(lines: &[Line]) -> BTreeMap<Point, Vec<SegmentId>> {
+    let mut crossings: BTreeMap<Point, Vec<SegmentId>> = BTreeMap::new();
+    pairs(lines, |point, segment, other_segment| {
+        crossings
+            .entry(point)
+            .or_default()
+            .extend([SegmentId::new(segment), SegmentId::new(other_segment)]);
+    });
+    for segments in crossings.values_mut() {
+        segments.sort_unstable();
+        segments.dedup();
+    }
+    crossings
+}
+
+/// Calls `report` with the rounded point and the positions of both segments of every intersecting pair.
+fn pairs(lines: &[Line], mut report: impl FnMut(Point, usize, usize)) {
+    for (index, line) in lines.iter().enumerate() {
+        for (other_index, other_line) in lines.iter().enumerate().skip(index + 1) {
+            if let Some(point) = line.intersection(other_line) {
+                report(point.round(9), index, other_index);
+            }
+        }
+    }
+}
+
+/// Points of `points` without a point of `other` within `tolerance` in both coordinates.
+///
+/// Rounding the same intersection computed from either segment may differ in the last decimal,
+/// so results are compared with a tolerance of a few units of the rounding.
+pub fn missing<'a>(
+    points: &'a BTreeSet<Point>,
+    other: &BTreeSet<Point>,
+    tolerance: f64,
+) -> Vec<&'a Point> {
+    points
+        .iter()
+        .filter(|point| {
+            let from = Point {
+                x: point.x - tolerance,
+                y: f64::NEG_INFINITY,
+            };
+            let to = Point {
+                x: point.x + tolerance,
+                y: f64::INFINITY,
+            };
+            !other
+                .range(from..=to)
+                .any(|candidate| (candidate.y - point.y).abs() <= tolerance)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{event_queue::EventQueue, input::read_file};
+
+    #[test]
+    fn test_same_as_sweep() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let expected = intersections(&lines);
+
+        let found = EventQueue::new(lines).sweep();
+        assert_eq!(expected.len(), 796);
+        assert_eq!(found.len(), 796);
+        assert!(missing(&expected, &found, 1e-8).is_empty());
+        assert!(missing(&found, &expected, 1e-8).is_empty());
+    }
+
+    #[test]
+    fn test_crossings() {
+        let lines: Vec<Line> = ["0 0 4 4", "0 4 4 0", "0 2 4 2", "5 0 6 1"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let crossings = crossings(&lines);
+        assert_eq!(
+            crossings[&Point { x: 2.0, y: 2.0 }],
+            [0, 1, 2].map(SegmentId::new)
+        );
+        assert!(crossings.keys().eq(&intersections(&lines)));
+    }
+
+    #[test]
+    fn test_missing() {
+        let points = BTreeSet::from([Point { x: 1.0, y: 2.0 }, Point { x: 3.0, y: 4.0 }]);
+        let other = BTreeSet::from([Point {
+            x: 1.0 + 1e-9,
+            y: 2.0 - 1e-9,
+        }]);
+        assert_eq!(
+            missing(&points, &other, 1e-8),
+            vec![&Point { x: 3.0, y: 4.0 }]
+        );
+        assert_eq!(missing(&points, &other, 0.0).len(), 2);
+    }
+}
@@ -0,0 +1,333 @@
+//! Collects the segments of one input file for [`algorithm::run`](crate::algorithm::run).
+//!
+//! Segments are added to the event queue while they are read, so counting the intersections of a large file does
+//! not keep its segments in memory.
+
+use std::{
+    io::BufRead,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+
+use crate::{
+    arc::Arc,
+    binary,
+    direction::{DirectedQueue, Rotation},
+    event_queue::{EventQueue, InvalidSegment},
+    geometry::{Bounds, Line},
+    gis,
+    input::{InputFormat, ReadError, SegmentReader},
+    orthogonal,
+    output::SegmentInfo,
+    polyline::Polyline,
+    primitive::{Primitive, CLIP_MARGIN},
+};
+
+/// Segments of one input file, only collected if an output needs them.
+#[derive(Debug, Default)]
+pub struct Segments {
+    pub lines: Vec<Line>,
+    /// Feature of every segment by position, only known for WKT and GeoJSON input
+    pub features: Vec<String>,
+}
+
+impl Segments {
+    pub fn push(&mut self, line: Line, feature: Option<String>) {
+        self.lines.push(line);
+        if let Some(feature) = feature {
+            self.features.push(feature);
+        }
+    }
+
+    /// The segment at position `id` for the output.
+    pub fn info(&self, id: usize) -> SegmentInfo<'_> {
+        SegmentInfo {
+            id,
+            line: &self.lines[id],
+            feature: self.features.get(id).map(String::as_str),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CollectOptions {
+    /// Skip invalid lines and segments with a warning instead of failing
+    pub skip_invalid: bool,
+    /// Keep a copy of every segment added to the event queue in [`Input::segments`]
+    pub keep_lines: bool,
+    /// Add the segments to a queue sweeping along this direction in degrees instead of the x axis
+    pub direction: Option<f64>,
+    /// Collect the segments for the orthogonal sweep while all of them are axis-parallel
+    pub orthogonal: bool,
+    /// Measure the time spent in the event queue, see [`Input::init`]
+    pub timed: bool,
+}
+
+/// The segments of one input file, ready to be swept.
+#[derive(Default)]
+pub struct Input {
+    pub queue: EventQueue,
+    /// The queue of [`CollectOptions::direction`], which replaces `queue`
+    pub directed: Option<DirectedQueue>,
+    /// The segments added to the queue if they are kept, and the segments of all feature paths
+    pub segments: Segments,
+    /// The axis-parallel segments collected for the orthogonal sweep, `None` once another segment was read
+    pub orthogonal: Option<Segments>,
+    pub arcs: Vec<Arc>,
+    /// The paths of WKT and GeoJSON features, swept as polylines
+    pub polylines: Vec<Polyline>,
+    /// Position of the first segment of every path in `segments`, the segments are numbered through all paths
+    pub first_segments: Vec<usize>,
+    /// Number of segments added to the queue
+    pub count: usize,
+    /// Invalid segments skipped with a warning
+    pub skipped: usize,
+    /// The invalid segment which fails the input, unless they are skipped
+    pub invalid: Option<InvalidSegment>,
+    /// Time spent in the event queue, only measured with [`CollectOptions::timed`]
+    pub init: Duration,
+    options: CollectOptions,
+}
+
+impl Input {
+    pub fn new(options: CollectOptions) -> Self {
+        Self {
+            directed: options
+                .direction
+                .map(|degrees| DirectedQueue::new(Rotation::from_degrees(degrees))),
+            orthogonal: options.orthogonal.then(Segments::default),
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a segment to the queue, or collects it for the orthogonal sweep while all segments are axis-parallel.
+    ///
+    /// The orthogonal sweep allows vertical segments and shared end points, the first other segment moves the
+    /// collected segments to the event queue.
+    pub fn add(&mut self, line: Line) {
+        if let Some(collected) = &mut self.orthogonal {
+            if orthogonal::is_axis_parallel(&line) {
+                collected.push(line, None);
+                return;
+            }
+            self.enqueue_orthogonal();
+        }
+        self.enqueue(line);
+    }
+
+    /// Moves the segments collected for the orthogonal sweep to the event queue, for the outputs it does not support.
+    pub fn enqueue_orthogonal(&mut self) {
+        for line in self
+            .orthogonal
+            .take()
+            .into_iter()
+            .flat_map(|lines| lines.lines)
+        {
+            self.enqueue(line);
+        }
+    }
+
+    /// Adds the segment to the queue, after the first invalid segment only counts the skipped ones.
+    fn enqueue(&mut self, line: Line) {
+        if self.invalid.is_some() {
+            return;
+        }
+        let kept = self.options.keep_lines.then(|| line.clone());
+        let start = self.options.timed.then(Instant::now);
+        let result = match &mut self.directed {
+            Some(directed) => directed.try_add_line(line),
+            None => self.queue.try_add_line(line),
+        };
+        if let Some(start) = start {
+            self.init += start.elapsed();
+        }
+        match result {
+            Ok(()) => {
+                self.count += 1;
+                if let Some(line) = kept {
+                    self.segments.push(line, None);
+                }
+            }
+            Err(error) if self.options.skip_invalid => {
+                warn!("Skipping {}", error);
+                self.skipped += 1;
+            }
+            Err(error) => self.invalid = Some(error),
+        }
+    }
+}
+
+/// Reads the input in the given format, polylines are read by [`SegmentReader::read_polylines`] instead.
+///
+/// Rays and lines of the segment format are clipped to the bounding box of the whole input, the segments from the
+/// first of them on wait for it. The paths of features are not added to the queue, their segments share vertices.
+pub fn read<R: BufRead>(
+    reader: SegmentReader<R>,
+    format: InputFormat,
+    options: CollectOptions,
+) -> Result<Input, ReadError> {
+    let mut input = Input::new(options);
+    let name = reader.name().to_string();
+    match format {
+        InputFormat::Segments => {
+            let mut bounds: Option<Bounds> = None;
+            let mut pending = Vec::new();
+            let summary =
+                reader
+                    .skip_invalid(options.skip_invalid)
+                    .read_primitives(|primitive| {
+                        let corners = primitive.bounds();
+                        match &mut bounds {
+                            Some(bounds) => {
+                                bounds.extend(&corners.min);
+                                bounds.extend(&corners.max);
+                            }
+                            None => bounds = Some(corners),
+                        }
+                        match primitive {
+                            Primitive::Segment(line) if pending.is_empty() => input.add(line),
+                            Primitive::Arc(arc) => input.arcs.push(arc),
+                            primitive => pending.push(primitive),
+                        }
+                    })?;
+            if summary.skipped > 0 {
+                warn!("Skipped {} invalid lines", summary.skipped);
+            }
+            if let Some(bounds) = bounds.filter(|_| !pending.is_empty()) {
+                let bounds = bounds.padded(CLIP_MARGIN);
+                info!(
+                    "Clipping rays and lines to the bounding box from {} to {}",
+                    bounds.min, bounds.max
+                );
+                for line in pending
+                    .iter()
+                    .filter_map(|primitive| primitive.clip(&bounds))
+                {
+                    input.add(line);
+                }
+            }
+        }
+        InputFormat::Wkt | InputFormat::GeoJson => {
+            let reader = reader.into_inner();
+            let features = match format {
+                InputFormat::Wkt => gis::read_wkt(&name, reader),
+                _ => gis::read_geojson(&name, reader),
+            }?;
+            for feature in features {
+                for path in feature.paths {
+                    input.first_segments.push(input.segments.lines.len());
+                    for line in path.segments() {
+                        input.segments.push(line, Some(feature.id.clone()));
+                    }
+                    input.polylines.push(path);
+                }
+            }
+            input.count = input.segments.lines.len();
+        }
+        InputFormat::Binary => {
+            binary::read_segments(reader.into_inner(), |line| input.add(line))
+                .map_err(|err| ReadError::Format(format!("Failed to read {}: {}", name, err)))?;
+        }
+        InputFormat::Polylines => {
+            panic!("polylines are read by SegmentReader::read_polylines")
+        }
+    }
+    Ok(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::Point;
+
+    fn read_text(text: &str, format: InputFormat, options: CollectOptions) -> Input {
+        read(SegmentReader::new("test", text.as_bytes()), format, options).unwrap()
+    }
+
+    #[test]
+    fn test_orthogonal_collection() {
+        let options = CollectOptions {
+            orthogonal: true,
+            ..Default::default()
+        };
+        let input = read_text("0 1 4 1\n2 0 2 3\n", InputFormat::Segments, options);
+        assert_eq!(input.orthogonal.unwrap().lines.len(), 2);
+        assert_eq!(input.count, 0);
+
+        // the first other segment moves them to the queue, which rejects the vertical one
+        let input = read_text(
+            "0 1 4 1\n2 0 2 3\n0 0 3 3\n",
+            InputFormat::Segments,
+            options,
+        );
+        assert!(input.orthogonal.is_none());
+        assert_eq!(input.count, 1);
+        assert!(matches!(input.invalid, Some(InvalidSegment::Vertical(_))));
+
+        let skip = CollectOptions {
+            skip_invalid: true,
+            keep_lines: true,
+            ..options
+        };
+        let input = read_text("0 1 4 1\n2 0 2 3\n0 0 3 3\n", InputFormat::Segments, skip);
+        assert_eq!((input.count, input.skipped), (2, 1));
+        assert!(input.invalid.is_none());
+        assert_eq!(input.segments.lines[1], "0 0 3 3".parse().unwrap());
+    }
+
+    #[test]
+    fn test_read_primitives() {
+        let options = CollectOptions {
+            keep_lines: true,
+            ..Default::default()
+        };
+        // the line is clipped to the bounding box of all primitives, the segments after it wait for the box
+        let input = read_text(
+            "0 0 4 4\nline 0 2 1 2\n0 4 4 0\narc 2 2 1 0 180\n",
+            InputFormat::Segments,
+            options,
+        );
+        assert_eq!(input.count, 3);
+        assert_eq!(input.arcs.len(), 1);
+        assert_eq!(input.segments.lines[2], "0 4 4 0".parse().unwrap());
+        let clipped = &input.segments.lines[1];
+        assert_eq!((clipped.p.y, clipped.q.y), (2.0, 2.0));
+        assert!(clipped.p.x < 0.0 && clipped.q.x > 4.0);
+
+        let timed = CollectOptions {
+            direction: Some(90.0),
+            timed: true,
+            ..options
+        };
+        let input = read_text("1 0 1 2\n", InputFormat::Segments, timed);
+        assert!(input.directed.is_some() && input.invalid.is_none());
+        assert!(input.init > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_read_features() {
+        let input = read_text(
+            "LINESTRING (0 0, 2 2, 4 0)\nLINESTRING (0 1, 4 1)\n",
+            InputFormat::Wkt,
+            CollectOptions::default(),
+        );
+        assert_eq!(input.polylines.len(), 2);
+        assert_eq!(input.first_segments, [0, 2]);
+        assert_eq!(input.count, 3);
+        assert_eq!(input.segments.features, ["1", "1", "2"]);
+        let info = input.segments.info(1);
+        assert_eq!((info.id, info.feature), (1, Some("1")));
+        assert_eq!(info.line.q, Point { x: 4.0, y: 0.0 });
+
+        let error = read(
+            SegmentReader::new("test.bin", &b"not binary"[..]),
+            InputFormat::Binary,
+            CollectOptions::default(),
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().starts_with("Failed to read test.bin"));
+    }
+}
@@ -0,0 +1,361 @@
+//! Sweep along an arbitrary direction instead of the x axis, so vertical segments can be swept as well.
+//!
+//! The segments are rotated so the sweep direction becomes the x axis and swept as usual. Every intersection
+//! found is then computed again from the original segments of its event, so the reported points are the same
+//! as for a sweep in the original coordinates, rounded to 9 decimals.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    arena::{Arena, SegmentId},
+    event_queue::{Event, EventQueue, InvalidSegment},
+    geometry::{Line, Point},
+    observer::{DiscardReason, NoopObserver, SweepObserver},
+    sweep_line::SweepLine,
+};
+
+/// Rotation which turns the sweep direction into the x axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation {
+    cos: f64,
+    sin: f64,
+}
+
+impl Rotation {
+    /// Sweep direction as counterclockwise angle to the x axis in degrees, `0` is the usual sweep.
+    pub fn from_degrees(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self { cos, sin }
+    }
+
+    /// Coordinates of the point with the sweep direction as x axis.
+    pub fn apply(&self, point: &Point) -> Point {
+        Point {
+            x: point.x * self.cos + point.y * self.sin,
+            y: point.y * self.cos - point.x * self.sin,
+        }
+    }
+
+    /// Original coordinates of a rotated point.
+    pub fn invert(&self, point: &Point) -> Point {
+        Point {
+            x: point.x * self.cos - point.y * self.sin,
+            y: point.x * self.sin + point.y * self.cos,
+        }
+    }
+
+    pub fn apply_line(&self, line: &Line) -> Line {
+        Line {
+            p: self.apply(&line.p),
+            q: self.apply(&line.q),
+        }
+    }
+}
+
+/// Event queue of the rotated segments, which keeps the original segments to report the intersections.
+pub struct DirectedQueue {
+    rotation: Rotation,
+    queue: EventQueue,
+    /// Original segments, under the ids of their rotated copies in the queue
+    lines: Arena,
+}
+
+impl DirectedQueue {
+    pub fn new(rotation: Rotation) -> Self {
+        Self {
+            rotation,
+            queue: EventQueue::default(),
+            lines: Arena::default(),
+        }
+    }
+
+    /// Like [`EventQueue::try_add_line`] for the rotated segment, which is vertical if the original segment is
+    /// perpendicular to the sweep direction. The error contains the original segment.
+    pub fn try_add_line(&mut self, line: Line) -> Result<(), InvalidSegment> {
+        match self.queue.try_add_line(self.rotation.apply_line(&line)) {
+            Ok(()) => {
+                self.lines.push(line);
+                Ok(())
+            }
+            Err(InvalidSegment::Vertical(_)) => Err(InvalidSegment::Vertical(line)),
+            Err(InvalidSegment::DuplicatePoint(_, point)) => Err(InvalidSegment::DuplicatePoint(
+                line,
+                self.rotation.invert(&point).round(9),
+            )),
+        }
+    }
+
+    /// Same result as [`EventQueue::sweep`] in the original coordinates, for segments which may be vertical.
+    pub fn sweep(self) -> BTreeSet<Point> {
+        self.sweep_with_observer(&mut NoopObserver)
+    }
+
+    /// Like [`EventQueue::sweep_with_observer`], the observer sees the original segments and points.
+    ///
+    /// Only the sweep line passed to [`SweepObserver::event_handled`] holds the positions of the rotated segments.
+    pub fn sweep_with_observer<O: SweepObserver>(self, observer: &mut O) -> BTreeSet<Point> {
+        let mut original = Original {
+            rotation: self.rotation,
+            lines: &self.lines,
+            observer,
+            event: None,
+            scheduled: BTreeMap::new(),
+            points: BTreeSet::new(),
+        };
+        self.queue.sweep_with_observer(&mut original);
+        original.points
+    }
+}
+
+/// Forwards every step of the rotated sweep with the original segments, and collects the intersections of the
+/// intersection events computed from the original segments.
+struct Original<'a, O> {
+    rotation: Rotation,
+    lines: &'a Arena,
+    observer: &'a mut O,
+    /// The event being handled, in original coordinates
+    event: Option<Event>,
+    /// Original point of every scheduled intersection by its rotated point, until its event is handled, so all
+    /// steps at one rotated point see the same original point
+    scheduled: BTreeMap<Point, Point>,
+    points: BTreeSet<Point>,
+}
+
+impl<O> Original<'_, O> {
+    /// Intersection of the original segments, rounded like in the sweep.
+    fn intersection(&self, point: &Point, segment: SegmentId, other_segment: SegmentId) -> Point {
+        if let Some(original) = self.scheduled.get(point) {
+            return original.clone();
+        }
+        // the rotated segments may just touch where the original ones barely miss each other
+        self.lines[segment]
+            .intersection(&self.lines[other_segment])
+            .unwrap_or_else(|| self.rotation.invert(point))
+            .round(9)
+    }
+
+    /// The end point of the original segment which was rotated into `point`.
+    fn end_point(&self, point: &Point, segment: SegmentId) -> Point {
+        let line = &self.lines[segment];
+        if self.rotation.apply(&line.p) == *point {
+            line.p.clone()
+        } else {
+            line.q.clone()
+        }
+    }
+
+    fn original(&self, event: &Event) -> Event {
+        match event {
+            Event::Begin { point, segment } => Event::Begin {
+                point: self.end_point(point, *segment),
+                segment: *segment,
+            },
+            Event::End { point, segment } => Event::End {
+                point: self.end_point(point, *segment),
+                segment: *segment,
+            },
+            Event::Intersection {
+                point,
+                segment,
+                other_segment,
+            } => Event::Intersection {
+                point: self.intersection(point, *segment, *other_segment),
+                segment: *segment,
+                other_segment: *other_segment,
+            },
+        }
+    }
+}
+
+impl<O: SweepObserver> SweepObserver for Original<'_, O> {
+    fn event_popped(&mut self, event: &Event) {
+        let event = self.original(event);
+        if let Event::Intersection { point, .. } = &event {
+            self.points.insert(point.clone());
+        }
+        self.observer.event_popped(&event);
+        self.event = Some(event);
+    }
+
+    fn segment_inserted(&mut self, segment: SegmentId, _segments: &Arena) {
+        self.observer.segment_inserted(segment, self.lines);
+    }
+
+    fn segment_removed(&mut self, segment: SegmentId, _segments: &Arena) {
+        self.observer.segment_removed(segment, self.lines);
+    }
+
+    fn segments_swapped(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+        let point = self.intersection(point, segment, other_segment);
+        self.observer
+            .segments_swapped(&point, segment, other_segment, self.lines);
+    }
+
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+        let original = self.intersection(point, segment, other_segment);
+        self.scheduled.insert(point.clone(), original.clone());
+        self.observer
+            .intersection_scheduled(&original, segment, other_segment, self.lines);
+    }
+
+    fn intersection_discarded(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        let point = self.intersection(point, segment, other_segment);
+        self.observer
+            .intersection_discarded(&point, segment, other_segment, self.lines, reason);
+    }
+
+    fn event_handled(&mut self, event: &Event, sweep_line: &SweepLine, _segments: &Arena) {
+        if let Event::Intersection { point, .. } = event {
+            self.scheduled.remove(point);
+        }
+        if let Some(event) = self.event.take() {
+            self.observer.event_handled(&event, sweep_line, self.lines);
+        }
+    }
+}
+
+/// Intersections of the segments, swept along the direction given in degrees.
+///
+/// Panics if a segment is perpendicular to the direction or violates another requirement of the sweep.
+pub fn sweep(lines: &[Line], degrees: f64) -> BTreeSet<Point> {
+    let mut queue = DirectedQueue::new(Rotation::from_degrees(degrees));
+    for line in lines {
+        if let Err(error) = queue.try_add_line(line.clone()) {
+            panic!("{}", error);
+        }
+    }
+    queue.sweep()
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{
+        brute_force,
+        generate::{generate, GenerateOptions},
+        input::read_file,
+        observer::{IntersectionCounter, IntersectionPairs},
+    };
+
+    /// The segments inserted into the sweep line, by id.
+    #[derive(Default)]
+    struct Inserted(BTreeMap<SegmentId, Line>);
+
+    impl SweepObserver for Inserted {
+        fn segment_inserted(&mut self, segment: SegmentId, segments: &Arena) {
+            let previous = self.0.insert(segment, segments[segment].clone());
+            assert!(previous.is_none());
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let rotation = Rotation::from_degrees(90.0);
+        let point = rotation.apply(&Point { x: 0.0, y: 2.0 });
+        assert!((point.x - 2.0).abs() < 1e-12 && point.y.abs() < 1e-12);
+        let back = rotation.invert(&point).round(9);
+        assert_eq!(back, Point { x: 0.0, y: 2.0 });
+    }
+
+    #[test]
+    fn test_same_as_sweep() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let expected = EventQueue::new(lines.clone()).sweep();
+        for degrees in [0.0, 30.0, 90.0, 200.0] {
+            assert_eq!(sweep(&lines, degrees), expected, "{} degrees", degrees);
+        }
+    }
+
+    #[test]
+    fn test_vertical_segments() {
+        // turn every other segment into a vertical one
+        let lines: Vec<_> = generate(&GenerateOptions {
+            count: 500,
+            size: 100.0,
+            seed: 3,
+            ..Default::default()
+        })
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let q = if index % 2 == 0 {
+                Point {
+                    x: line.p.x,
+                    y: line.q.y,
+                }
+            } else {
+                line.q
+            };
+            Line { p: line.p, q }
+        })
+        .collect();
+        let expected = brute_force::intersections(&lines);
+        assert!(!expected.is_empty());
+        assert_eq!(sweep(&lines, 1.0), expected);
+
+        // the observer sees the original segments and points
+        let mut queue = DirectedQueue::new(Rotation::from_degrees(1.0));
+        for line in &lines {
+            queue.try_add_line(line.clone()).unwrap();
+        }
+        let mut observer = (
+            (IntersectionPairs::default(), IntersectionCounter::default()),
+            Inserted::default(),
+        );
+        assert_eq!(queue.sweep_with_observer(&mut observer), expected);
+        let ((pairs, counter), inserted) = observer;
+        // every segment is inserted once under its id, and seen with its original coordinates
+        assert_eq!(inserted.0.len(), lines.len());
+        for (id, line) in &inserted.0 {
+            assert_eq!(line, &lines[id.index()]);
+        }
+        assert!(pairs.pairs.keys().eq(&expected));
+        for (point, &(segment, other_segment)) in &pairs.pairs {
+            let (line, other_line) = (&lines[segment.index()], &lines[other_segment.index()]);
+            assert_eq!(&line.intersection(other_line).unwrap().round(9), point);
+        }
+        let counts = counter.segment_counts(lines.len()).counts;
+        let expected_counts: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let points: BTreeSet<_> = lines
+                    .iter()
+                    .filter(|other_line| other_line != &line)
+                    .filter_map(|other_line| line.intersection(other_line))
+                    .map(|point| point.round(9))
+                    .collect();
+                points.len()
+            })
+            .collect();
+        assert_eq!(counts, expected_counts);
+
+        let mut queue = DirectedQueue::new(Rotation::from_degrees(0.0));
+        let vertical = Line::from_str("1 0 1 2").unwrap();
+        assert_eq!(
+            queue.try_add_line(vertical.clone()),
+            Err(InvalidSegment::Vertical(vertical))
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Intersection index for a changing set of segments, which avoids a full sweep after every change.
+//!
+//! The segments are bucketed in a uniform grid like in [`crate::grid`], so inserting a segment only tests it against the
+//! segments sharing a grid cell it passes through.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    arena::{Arena, SegmentId},
+    geometry::{Line, Point},
+    grid,
+};
+
+/// Intersections which appeared or disappeared with one insertion or removal, both sorted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Change {
+    pub added: Vec<Point>,
+    pub removed: Vec<Point>,
+}
+
+/// Keeps the intersections of a set of segments up to date while segments are inserted and removed.
+///
+/// Intersections are rounded to 9 decimals like in the sweep, so [`DynamicIndex::intersections`]
+/// equals the result of sweeping the current segments.
+pub struct DynamicIndex {
+    cell_size: f64,
+    segments: Arena,
+    /// Intersections of every segment with other segments by id, `None` once the segment is removed
+    crossings: Vec<Option<BTreeMap<SegmentId, Point>>>,
+    /// Ids of removed segments, handed out again by the next insertions so the index does not grow with churn
+    free: Vec<SegmentId>,
+    cells: HashMap<(i64, i64), Vec<SegmentId>>,
+    /// Number of segment pairs intersecting in each point
+    points: BTreeMap<Point, usize>,
+}
+
+impl DynamicIndex {
+    /// Grid cells of about the length of a typical segment keep the number of candidates per insertion low.
+    pub fn new(cell_size: f64) -> Self {
+        assert!(cell_size > 0.0, "the cell size must be positive");
+        Self {
+            cell_size,
+            segments: Arena::default(),
+            crossings: Vec::new(),
+            free: Vec::new(),
+            cells: HashMap::new(),
+            points: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the segment and returns its id, which stays valid until the segment is removed.
+    ///
+    /// The ids of removed segments are reused, so the memory of the index is bounded by the largest number of segments
+    /// it held at once.
+    pub fn insert(&mut self, line: Line) -> (SegmentId, Change) {
+        let mut candidates = BTreeSet::new();
+        let cells: Vec<_> = grid::cells(&line, self.cell_size).collect();
+        for cell in &cells {
+            if let Some(segments) = self.cells.get(cell) {
+                candidates.extend(segments.iter().copied());
+            }
+        }
+
+        let mut crossings = BTreeMap::new();
+        let mut change = Change::default();
+        for other in candidates {
+            let Some(point) = line.intersection(&self.segments[other]) else {
+                continue;
+            };
+            let point = point.round(9);
+            let count = self.points.entry(point.clone()).or_default();
+            *count += 1;
+            if *count == 1 {
+                change.added.push(point.clone());
+            }
+            crossings.insert(other, point);
+        }
+
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.segments.replace(id, line);
+                id
+            }
+            None => {
+                self.crossings.push(None);
+                self.segments.push(line)
+            }
+        };
+        for (other, point) in &crossings {
+            if let Some(other_crossings) = &mut self.crossings[other.index()] {
+                other_crossings.insert(id, point.clone());
+            }
+        }
+        self.crossings[id.index()] = Some(crossings);
+        for cell in cells {
+            self.cells.entry(cell).or_default().push(id);
+        }
+
+        change.added.sort();
+        (id, change)
+    }
+
+    /// Removes the segment, `None` if it is not in the index.
+    pub fn remove(&mut self, id: SegmentId) -> Option<Change> {
+        let crossings = self.crossings.get_mut(id.index())?.take()?;
+
+        let cells: Vec<_> = grid::cells(&self.segments[id], self.cell_size).collect();
+        for cell in cells {
+            if let Some(segments) = self.cells.get_mut(&cell) {
+                segments.retain(|&segment| segment != id);
+                if segments.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+
+        let mut change = Change::default();
+        for (other, point) in crossings {
+            if let Some(other_crossings) = &mut self.crossings[other.index()] {
+                other_crossings.remove(&id);
+            }
+            let count = self.points.get_mut(&point).expect("counted on insertion");
+            *count -= 1;
+            if *count == 0 {
+                self.points.remove(&point);
+                change.removed.push(point);
+            }
+        }
+        change.removed.sort();
+        self.free.push(id);
+        Some(change)
+    }
+
+    pub fn get(&self, id: SegmentId) -> Option<&Line> {
+        self.crossings
+            .get(id.index())?
+            .as_ref()
+            .map(|_| &self.segments[id])
+    }
+
+    /// The segments currently in the index.
+    pub fn segments(&self) -> impl Iterator<Item = (SegmentId, &Line)> {
+        self.segments
+            .iter()
+            .filter(|(id, _)| self.crossings[id.index()].is_some())
+    }
+
+    /// Intersections of the segment with the other segments in the index.
+    pub fn crossings(&self, id: SegmentId) -> Option<&BTreeMap<SegmentId, Point>> {
+        self.crossings.get(id.index())?.as_ref()
+    }
+
+    /// All current intersections, like [`EventQueue::sweep`](crate::event_queue::EventQueue::sweep) would find them.
+    pub fn intersections(&self) -> impl Iterator<Item = &Point> {
+        self.points.keys()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{
+        event_queue::EventQueue,
+        generate::{generate, GenerateOptions, Rng},
+    };
+
+    #[test]
+    fn test_insert_remove() {
+        let mut index = DynamicIndex::new(1.0);
+        let (a, change) = index.insert(Line::from_str("0 0 4 4").unwrap());
+        assert_eq!(change, Change::default());
+
+        let (b, change) = index.insert(Line::from_str("0 4 4 0").unwrap());
+        assert_eq!(change.added, [Point { x: 2.0, y: 2.0 }]);
+        assert_eq!(index.crossings(a).unwrap()[&b], Point { x: 2.0, y: 2.0 });
+
+        // far away, in other grid cells
+        let (c, change) = index.insert(Line::from_str("10 10 11 12").unwrap());
+        assert!(change.added.is_empty());
+
+        let change = index.remove(a).unwrap();
+        assert_eq!(change.removed, [Point { x: 2.0, y: 2.0 }]);
+        assert!(index.crossings(b).unwrap().is_empty());
+        assert_eq!(index.remove(a), None);
+        assert_eq!(index.get(a), None);
+        assert_eq!(
+            index.segments().map(|(id, _)| id).collect::<Vec<_>>(),
+            [b, c]
+        );
+        assert_eq!(index.intersections().count(), 0);
+
+        // the id of the removed segment is handed out again instead of growing the index
+        let (d, change) = index.insert(Line::from_str("0 0 4 4").unwrap());
+        assert_eq!(d, a);
+        assert_eq!(change.added, [Point { x: 2.0, y: 2.0 }]);
+        assert_eq!(index.crossings(b).unwrap()[&d], Point { x: 2.0, y: 2.0 });
+        assert_eq!(index.segments.len(), 3);
+    }
+
+    #[test]
+    fn test_same_as_sweep() {
+        let lines = generate(&GenerateOptions {
+            count: 400,
+            size: 100.0,
+            seed: 5,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut index = DynamicIndex::new(10.0);
+        let mut ids = Vec::new();
+        let mut rng = Rng::new(5);
+        let mut expected = BTreeSet::new();
+
+        for (step, line) in lines.into_iter().enumerate() {
+            let before = expected.clone();
+            let change = if step % 3 == 2 {
+                // remove a random segment every third step
+                let id = ids.swap_remove(rng.next_u64() as usize % ids.len());
+                index.remove(id).unwrap()
+            } else {
+                let (id, change) = index.insert(line);
+                ids.push(id);
+                change
+            };
+
+            let current = ids
+                .iter()
+                .map(|&id| index.get(id).unwrap().clone())
+                .collect();
+            expected = EventQueue::new(current).sweep();
+            let found: BTreeSet<_> = index.intersections().cloned().collect();
+            assert_eq!(found, expected, "step {}", step);
+
+            let added: Vec<_> = expected.difference(&before).cloned().collect();
+            let removed: Vec<_> = before.difference(&expected).cloned().collect();
+            assert_eq!(change, Change { added, removed }, "step {}", step);
+        }
+    }
+}
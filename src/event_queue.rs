@@ -1,27 +1,32 @@
 use std::{
     cmp::{max, min},
     collections::BTreeSet,
+    error::Error,
+    fmt::{self, Display},
 };
 
 use crate::{
+    arena::{Arena, SegmentId},
     geometry::{Line, Point},
-    sweep_line::SweepLine,
+    observer::{DiscardReason, NoopObserver, SweepObserver},
+    sweep_line::{SweepLine, SweepLineElement},
 };
 
-#[derive(Debug)]
+/// An event of the sweep, the segments are stored in the [`Arena`] of the queue.
+#[derive(Debug, Clone)]
 pub enum Event {
     Begin {
         point: Point,
-        line: Line,
+        segment: SegmentId,
     },
     End {
         point: Point,
-        line: Line,
+        segment: SegmentId,
     },
     Intersection {
         point: Point,
-        line: Line,
-        other_line: Line,
+        segment: SegmentId,
+        other_segment: SegmentId,
     },
 }
 
@@ -64,51 +69,98 @@ impl Ord for Event {
     }
 }
 
+/// A segment which violates the requirements of the sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidSegment {
+    Vertical(Line),
+    /// The point is already the begin or end of another segment.
+    DuplicatePoint(Line, Point),
+}
+
+impl Display for InvalidSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidSegment::Vertical(line) => write!(f, "vertical segment {}", line),
+            InvalidSegment::DuplicatePoint(line, point) => {
+                write!(f, "duplicate point {} of segment {}", point, line)
+            }
+        }
+    }
+}
+
+impl Error for InvalidSegment {}
+
 pub struct EventQueue {
     last_x: f64,
     queue: BTreeSet<Event>,
+    segments: Arena,
     pub intersection_points: BTreeSet<Point>,
+    /// Forget every intersection once the sweep line passed it, see [`EventQueue::count`]
+    count_only: bool,
+    intersection_count: usize,
 }
 
-impl EventQueue {
-    pub fn new(lines: Vec<Line>) -> Self {
-        let mut events: EventQueue = Self {
-            last_x: 0.0,
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self {
+            // segments may start at negative x
+            last_x: f64::NEG_INFINITY,
             queue: BTreeSet::new(),
+            segments: Arena::default(),
             intersection_points: BTreeSet::new(),
-        };
+            count_only: false,
+            intersection_count: 0,
+        }
+    }
+}
 
+impl EventQueue {
+    pub fn new(lines: Vec<Line>) -> Self {
+        let mut events = EventQueue::default();
         for line in lines {
-            if line.p.x == line.q.x {
-                panic!("Vertical line detected: {:?}", line)
-            }
+            events.add_line(line);
+        }
+        events
+    }
 
-            if line.len() < 0.0 {
-                panic!("Line segment with 0 length detected: {:?}", line)
-            }
+    /// Adds the begin and end event of a segment, allows building the queue while the input is still being read.
+    ///
+    /// Panics if the segment violates the requirements of the sweep, see [`EventQueue::try_add_line`].
+    pub fn add_line(&mut self, line: Line) {
+        if let Err(error) = self.try_add_line(line) {
+            panic!("{}", error)
+        }
+    }
 
-            let smaller = min(&line.p, &line.q);
-            let larger = max(&line.p, &line.q);
+    /// Like [`EventQueue::add_line`], but rejects vertical segments and points already in the queue
+    /// without changing the queue.
+    pub fn try_add_line(&mut self, line: Line) -> Result<(), InvalidSegment> {
+        if line.p.x == line.q.x {
+            return Err(InvalidSegment::Vertical(line));
+        }
 
-            let start = Event::Begin {
-                point: smaller.to_owned(),
-                line: line.clone(),
-            };
-            if events.queue.contains(&start) {
-                panic!("Duplicate point detected: {:?}", start)
-            }
-            events.queue.insert(start);
+        let smaller = min(&line.p, &line.q).to_owned();
+        let larger = max(&line.p, &line.q).to_owned();
+        let segment = self.segments.push(line);
 
-            let end = Event::End {
-                point: larger.to_owned(),
-                line,
-            };
-            if events.queue.contains(&end) {
-                panic!("Duplicate point detected: {:?}", end)
+        let start = Event::Begin {
+            point: smaller,
+            segment,
+        };
+        let end = Event::End {
+            point: larger,
+            segment,
+        };
+        for event in [&start, &end] {
+            if self.queue.contains(event) {
+                let line = self.segments.pop().expect("the segment was just pushed");
+                return Err(InvalidSegment::DuplicatePoint(line, event.point().clone()));
             }
-            events.queue.insert(end);
         }
-        events
+
+        self.queue.insert(start);
+        self.queue.insert(end);
+        Ok(())
     }
 
     pub fn pop_first(&mut self) -> Option<Event> {
@@ -125,90 +177,269 @@ impl EventQueue {
     pub fn add_intersection_event(
         &mut self,
         intersection_point: Point,
-        line: &Line,
-        other_line: &Line,
+        segment: SegmentId,
+        other_segment: SegmentId,
+    ) {
+        self.schedule_intersection(
+            intersection_point,
+            segment,
+            other_segment,
+            &mut NoopObserver,
+        );
+    }
+
+    /// The segments of the queue, ids are handed out in the order the segments were added.
+    pub fn segments(&self) -> &Arena {
+        &self.segments
+    }
+
+    /// Schedules the intersection of both segments, if they intersect.
+    fn check_intersection<O: SweepObserver>(
+        &mut self,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        observer: &mut O,
+    ) {
+        if let Some(inter) = self.segments[segment].intersection(&self.segments[other_segment]) {
+            self.schedule_intersection(inter, segment, other_segment, observer);
+        }
+    }
+
+    fn schedule_intersection<O: SweepObserver>(
+        &mut self,
+        intersection_point: Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        observer: &mut O,
     ) {
         let intersection_point = intersection_point.round(9);
-        if intersection_point.x > self.last_x
-            && !self.intersection_points.contains(&intersection_point)
-        {
-            self.intersection_points.insert(intersection_point.clone());
-            self.queue.insert(Event::Intersection {
-                point: intersection_point,
-                line: line.clone(),
-                other_line: other_line.clone(),
-            });
+        if intersection_point.x <= self.last_x {
+            observer.intersection_discarded(
+                &intersection_point,
+                segment,
+                other_segment,
+                &self.segments,
+                DiscardReason::BehindSweepLine,
+            );
+            return;
+        }
+        if self.intersection_points.contains(&intersection_point) {
+            observer.intersection_discarded(
+                &intersection_point,
+                segment,
+                other_segment,
+                &self.segments,
+                DiscardReason::Duplicate,
+            );
+            return;
         }
+
+        observer.intersection_scheduled(
+            &intersection_point,
+            segment,
+            other_segment,
+            &self.segments,
+        );
+        self.intersection_count += 1;
+        self.intersection_points.insert(intersection_point.clone());
+        self.queue.insert(Event::Intersection {
+            point: intersection_point,
+            segment,
+            other_segment,
+        });
     }
 
-    pub fn sweep(mut self) -> BTreeSet<Point> {
+    pub fn sweep(self) -> BTreeSet<Point> {
+        self.sweep_with_observer(&mut NoopObserver)
+    }
+
+    /// Same as [`EventQueue::sweep`], but reports every step of the sweep to the given observer.
+    pub fn sweep_with_observer<O: SweepObserver>(mut self, observer: &mut O) -> BTreeSet<Point> {
         let mut sweep_line = SweepLine::new();
+        self.handle_events(&mut sweep_line, f64::INFINITY, observer);
+        self.intersection_points
+    }
+
+    /// Number of intersections, like the length of [`EventQueue::sweep`], without keeping all intersections.
+    ///
+    /// Only the intersections right of the sweep line are kept to detect duplicates, those behind it are already
+    /// discarded by their position.
+    pub fn count(self) -> usize {
+        self.count_with_observer(&mut NoopObserver)
+    }
 
-        while let Some(event) = self.pop_first() {
-            sweep_line.update(event.point().x);
+    /// Same as [`EventQueue::count`], but reports every step of the sweep to the given observer.
+    pub fn count_with_observer<O: SweepObserver>(mut self, observer: &mut O) -> usize {
+        self.count_only = true;
+        let mut sweep_line = SweepLine::new();
+        self.handle_events(&mut sweep_line, f64::INFINITY, observer);
+        self.intersection_count
+    }
+
+    /// Sweeps the vertical slab `[from, to)` of the segments, for the parallel sweep in [`crate::parallel`].
+    ///
+    /// Segments starting left of the slab and ending right of `from` start in the sweep line, sorted by their y at `from`.
+    /// Returns the intersections with `from < x <= to`, so the results of adjacent slabs can be merged without duplicates.
+    pub fn sweep_slab<'a>(
+        lines: impl IntoIterator<Item = &'a Line>,
+        from: f64,
+        to: f64,
+    ) -> BTreeSet<Point> {
+        let mut queue = EventQueue {
+            last_x: from,
+            ..Default::default()
+        };
+        let mut sweep_line = SweepLine::new();
+        for line in lines {
+            let smaller = min(&line.p, &line.q);
+            let larger = max(&line.p, &line.q);
+            if larger.x <= from || smaller.x >= to {
+                continue;
+            }
+            let segment = queue.segments.push(line.clone());
+            if smaller.x >= from {
+                queue.queue.insert(Event::Begin {
+                    point: smaller.to_owned(),
+                    segment,
+                });
+            } else {
+                sweep_line.elements.push(SweepLineElement {
+                    y: line.y(from),
+                    segment,
+                });
+            }
+            // segments ending right of the slab are still in the sweep line when it stops
+            if larger.x < to {
+                queue.queue.insert(Event::End {
+                    point: larger.to_owned(),
+                    segment,
+                });
+            }
+        }
+        sweep_line.elements.sort();
+
+        let mut observer = NoopObserver;
+        for pair in sweep_line.elements.windows(2) {
+            queue.check_intersection(pair[0].segment, pair[1].segment, &mut observer);
+        }
+        queue.handle_events(&mut sweep_line, to, &mut observer);
+
+        // the sweep stopped before `to`, but intersections up to `to` were already scheduled
+        let mut intersections = queue.intersection_points;
+        intersections.retain(|point| point.x <= to);
+        intersections
+    }
+
+    /// Handles all events left of `to`.
+    fn handle_events<O: SweepObserver>(
+        &mut self,
+        sweep_line: &mut SweepLine,
+        to: f64,
+        observer: &mut O,
+    ) {
+        while self.queue.first().is_some_and(|event| event.point().x < to) {
+            let Some(event) = self.pop_first() else {
+                break;
+            };
+            observer.event_popped(&event);
+            sweep_line.update(event.point().x, &self.segments);
             match event {
-                Event::Begin { point, line } => {
-                    sweep_line.insert(point.y, line.clone());
+                Event::Begin { ref point, segment } => {
+                    sweep_line.insert(point.y, segment);
+                    observer.segment_inserted(segment, &self.segments);
 
-                    let neighbors = sweep_line.get_neighbors(&line);
+                    let neighbors = sweep_line.get_neighbors(segment);
                     let Some(neighbors) = neighbors else {
-                        panic!("Line not found in sweep line, but was just inserted: {:?}", line);
+                        panic!("Line not found in sweep line, but was just inserted: {:?}", self.segments[segment]);
                     };
 
                     if let Some(line_above) = neighbors.above {
-                        if let Some(inter) = line.intersection(&line_above.line) {
-                            self.add_intersection_event(inter, &line, &line_above.line);
-                        };
+                        self.check_intersection(segment, line_above.segment, observer);
                     };
 
                     if let Some(line_below) = neighbors.below {
-                        if let Some(inter) = line.intersection(&line_below.line) {
-                            self.add_intersection_event(inter, &line, &line_below.line);
-                        };
+                        self.check_intersection(segment, line_below.segment, observer);
                     };
                 }
-                Event::End { point: _, line } => {
-                    let neighbors = sweep_line.get_neighbors(&line);
+                Event::End { point: _, segment } => {
+                    let neighbors = sweep_line.get_neighbors(segment);
 
                     let Some(neighbors) = neighbors else {
-                        panic!("Line not found in sweep line, should be removed now: {:?}", line);
+                        panic!("Line not found in sweep line, should be removed now: {:?}", self.segments[segment]);
                     };
 
                     if let (Some(line_below), Some(line_above)) = (neighbors.below, neighbors.above)
                     {
-                        if let Some(inter) = line_below.line.intersection(&line_above.line) {
-                            self.add_intersection_event(inter, &line_below.line, &line_above.line);
-                        };
+                        self.check_intersection(line_below.segment, line_above.segment, observer);
                     };
 
-                    sweep_line.remove(&line);
+                    sweep_line.remove(segment);
+                    observer.segment_removed(segment, &self.segments);
                 }
                 Event::Intersection {
-                    point: intersection_point,
-                    line,
-                    other_line,
+                    point: ref intersection_point,
+                    segment,
+                    other_segment,
                 } => {
                     let swapped = sweep_line.swap_and_get_new_neighbors(
-                        &line,
-                        &other_line,
-                        &intersection_point,
+                        segment,
+                        other_segment,
+                        intersection_point,
+                        &self.segments,
+                    );
+                    observer.segments_swapped(
+                        intersection_point,
+                        segment,
+                        other_segment,
+                        &self.segments,
                     );
 
                     if let (line, Some(line_above)) = (swapped.bigger, swapped.above) {
-                        if let Some(inter) = line.line.intersection(&line_above.line) {
-                            self.add_intersection_event(inter, &line.line, &line_above.line);
-                        };
+                        self.check_intersection(line.segment, line_above.segment, observer);
                     };
 
                     if let (line, Some(line_below)) = (swapped.smaller, swapped.below) {
-                        if let Some(inter) = line.line.intersection(&line_below.line) {
-                            self.add_intersection_event(inter, &line.line, &line_below.line);
-                        };
+                        self.check_intersection(line.segment, line_below.segment, observer);
                     };
+
+                    if self.count_only {
+                        self.intersection_points.remove(intersection_point);
+                    }
                 }
             };
+            observer.event_handled(&event, sweep_line, &self.segments);
         }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_negative_x() {
+        let lines = ["-5 -1 -1 1", "-5 1 -1 -1"].map(|s| Line::from_str(s).unwrap());
+        let points = EventQueue::new(lines.to_vec()).sweep();
+
+        assert_eq!(points, BTreeSet::from([Point { x: -3.0, y: 0.0 }]));
+    }
+
+    #[test]
+    fn test_invalid_segments() {
+        let mut queue = EventQueue::default();
+        queue
+            .try_add_line(Line::from_str("0 0 2 2").unwrap())
+            .unwrap();
+        assert!(queue
+            .try_add_line(Line::from_str("1 0 1 2").unwrap())
+            .is_err());
+        assert!(queue
+            .try_add_line(Line::from_str("3 1 2 2").unwrap())
+            .is_err());
 
-        return self.intersection_points;
+        // the rejected segments did not change the queue
+        assert_eq!(queue.sweep().len(), 0);
     }
 }
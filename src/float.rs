@@ -0,0 +1,157 @@
+//! Hexadecimal floating point numbers like `0x1.8p+1`, which represent every `f64` exactly.
+
+use std::num::ParseFloatError;
+
+/// Formats the value like C's `%a`, e.g. `3.0` as `0x1.8p+1`. Infinity and NaN are written as by `Display`.
+pub fn to_hex(value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let bits = value.to_bits();
+    let sign = if bits >> 63 == 1 { "-" } else { "" };
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+
+    if exponent == 0 && mantissa == 0 {
+        return format!("{}0x0p+0", sign);
+    }
+    // subnormal numbers have no implicit leading 1
+    let (lead, exponent) = if exponent == 0 {
+        (0, -1022)
+    } else {
+        (1, exponent - 1023)
+    };
+
+    let fraction = format!("{:013x}", mantissa);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}0x{}p{:+}", sign, lead, exponent)
+    } else {
+        format!("{}0x{}.{}p{:+}", sign, lead, fraction, exponent)
+    }
+}
+
+/// Multiplies by `2^exponent` without overflowing in between.
+fn scale(mut value: f64, mut exponent: i32) -> f64 {
+    while exponent > 1000 {
+        value *= 2f64.powi(1000);
+        exponent -= 1000;
+    }
+    while exponent < -1000 {
+        value *= 2f64.powi(-1000);
+        exponent += 1000;
+    }
+    value * 2f64.powi(exponent)
+}
+
+/// Parses a hexadecimal float as written by [`to_hex`], the exponent is optional.
+///
+/// Longer literals are rounded to the nearest `f64`, digits beyond the precision of the mantissa only decide how
+/// ties are broken. Results below `f64::MIN_POSITIVE` may be rounded twice.
+pub fn parse_hex(s: &str) -> Option<f64> {
+    let (negative, s) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+
+    let (digits, exponent) = match s.find(['p', 'P']) {
+        Some(index) => (&s[..index], s[index + 1..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut mantissa = 0u64;
+    let mut exponent = exponent;
+    for (index, c) in integer.chars().chain(fraction.chars()).enumerate() {
+        let digit = c.to_digit(16)? as u64;
+        if mantissa >> 60 == 0 {
+            mantissa = mantissa << 4 | digit;
+            if index >= integer.len() {
+                exponent = exponent.checked_sub(4)?;
+            }
+        } else {
+            // digits beyond the precision of the mantissa only shift it, and are kept as sticky bit for rounding
+            if digit != 0 {
+                mantissa |= 1;
+            }
+            if index < integer.len() {
+                exponent = exponent.checked_add(4)?;
+            }
+        }
+    }
+
+    let value = scale(mantissa as f64, exponent);
+    Some(if negative { -value } else { value })
+}
+
+/// Parses decimal as well as hexadecimal floats.
+pub fn parse(s: &str) -> Result<f64, ParseFloatError> {
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        // the standard parser rejects hexadecimal floats, which gives us a fitting error
+        return parse_hex(s).ok_or_else(|| s.parse::<f64>().unwrap_err());
+    }
+    s.parse()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(3.0), "0x1.8p+1");
+        assert_eq!(to_hex(1.0), "0x1p+0");
+        assert_eq!(to_hex(-0.1), "-0x1.999999999999ap-4");
+        assert_eq!(to_hex(0.0), "0x0p+0");
+        assert_eq!(to_hex(f64::from_bits(1)), "0x0.0000000000001p-1022");
+        assert_eq!(to_hex(f64::INFINITY), "inf");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = [
+            0.1 + 0.2,
+            -1.0 / 3.0,
+            2.157894737,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::from_bits(1),
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            -0.0,
+            1e300,
+        ];
+        for value in values {
+            let parsed = parse(&to_hex(value)).unwrap();
+            assert_eq!(parsed.to_bits(), value.to_bits(), "{}", to_hex(value));
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("0x10"), Ok(16.0));
+        assert_eq!(parse("-0X.8P1"), Ok(-1.0));
+        assert_eq!(parse("0x1.8p+1"), Ok(3.0));
+        assert_eq!(parse("2.5"), Ok(2.5));
+        assert!(parse("0x").is_err());
+        assert!(parse("0x1.g").is_err());
+        assert!(parse("0x1p").is_err());
+
+        // exactly halfway between two values rounds to even, any digit after the tie rounds up
+        assert_eq!(parse("0x1.00000000000008p+0"), Ok(1.0));
+        assert_eq!(
+            parse("0x1.000000000000080000001p+0"),
+            Ok(1.0 + f64::EPSILON)
+        );
+        assert_eq!(
+            parse("0x1000000000000080000001"),
+            Ok(2f64.powi(84) + 2f64.powi(32))
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! Random data sets which meet the requirements of the sweep, like `generate_data.py`.
+//!
+//! Every segment is tested against the segments drawn before it and drawn again if the sweep could not handle it:
+//! vertical and collinear segments, shared points, events on one segment closer than [`MIN_EVENT_GAP`] in x (which
+//! includes more than two segments through about one point), and crossings too shallow for the sweep to order the two
+//! segments afterwards.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    f64::consts::{FRAC_PI_3, PI},
+    str::FromStr,
+};
+
+use crate::{
+    geometry::{Line, Point},
+    grid,
+    sweep_line::SWAP_OFFSET,
+};
+
+/// Small seedable pseudo random number generator (SplitMix64).
+///
+/// The same seed always yields the same data set, independent of the platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[from, to)`.
+    pub fn range(&mut self, from: f64, to: f64) -> f64 {
+        from + (to - from) * self.next_f64()
+    }
+
+    /// Standard normal distribution, using the Box-Muller transform.
+    pub fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
+
+/// How the segments are placed, see [`generate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Distribution {
+    /// Uniform start points, directions and lengths
+    #[default]
+    Uniform,
+    /// Short segments around Gaussian distributed cluster centers
+    Clustered,
+    /// Long, slightly tilted rows and columns spanning the whole area, about `n²/4` intersections
+    Grid,
+    /// Bundles of nearly parallel segments, which cross at very shallow angles
+    Bundles,
+    /// Bursts of segments through almost the same center point
+    Stars,
+    /// Every pair of segments crosses, `n(n-1)/2` intersections
+    Adversarial,
+}
+
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Distribution::Uniform),
+            "clustered" | "gaussian" => Ok(Distribution::Clustered),
+            "grid" => Ok(Distribution::Grid),
+            "bundles" => Ok(Distribution::Bundles),
+            "stars" => Ok(Distribution::Stars),
+            "adversarial" => Ok(Distribution::Adversarial),
+            _ => Err(format!("Unknown distribution: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateOptions {
+    pub distribution: Distribution,
+    pub count: usize,
+    /// The segments lie roughly in `[0, size)²`
+    pub size: f64,
+    /// Lengths of the short segments are in `]0, max_length]`
+    pub max_length: f64,
+    pub seed: u64,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            distribution: Distribution::default(),
+            count: 1000,
+            size: 1000.0,
+            max_length: 10.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Number of segments per bundle or star.
+const GROUP_SIZE: usize = 16;
+
+/// Attempts to draw one segment before [`generate`] gives up.
+const MAX_ATTEMPTS: usize = 10_000;
+
+/// Smallest distance in x between two events on one segment, far above the 9 decimals intersections are rounded to.
+pub const MIN_EVENT_GAP: f64 = 1e-7;
+
+/// The segments accepted so far, bucketed in grid cells to find the segments a new one may cross.
+struct Accepted {
+    lines: Vec<Line>,
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    /// End points and rounded intersections of every segment, its events in the sweep
+    events: Vec<BTreeSet<Point>>,
+    /// Events of all segments, the sweep merges equal points
+    points: BTreeSet<Point>,
+}
+
+impl Accepted {
+    fn new(cell_size: f64) -> Self {
+        Self {
+            lines: Vec::new(),
+            cell_size,
+            cells: HashMap::new(),
+            events: Vec::new(),
+            points: BTreeSet::new(),
+        }
+    }
+
+    /// The intersections of the segment with accepted segments by index, `None` if the sweep could not handle it next
+    /// to the accepted segments.
+    fn crossings(&self, line: &Line) -> Option<Vec<(usize, Point)>> {
+        if line.p.x == line.q.x {
+            return None;
+        }
+        let candidates: BTreeSet<_> = grid::cells(line, self.cell_size)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+
+        let mut crossings = Vec::new();
+        for index in candidates {
+            let other = &self.lines[index];
+            if line.is_collinear(other) {
+                return None;
+            }
+            let Some(point) = line.intersection(other) else {
+                continue;
+            };
+            let point = point.round(9);
+            if !separable(line, other, &point) || near(&self.events[index], &point) {
+                return None;
+            }
+            crossings.push((index, point));
+        }
+
+        let mut events: Vec<_> = crossings.iter().map(|(_, point)| point).collect();
+        events.extend([&line.p, &line.q]);
+        events.sort();
+        if events
+            .windows(2)
+            .any(|pair| pair[1].x - pair[0].x < MIN_EVENT_GAP)
+            || events.iter().any(|point| self.points.contains(point))
+        {
+            return None;
+        }
+        Some(crossings)
+    }
+
+    fn push(&mut self, line: Line, crossings: Vec<(usize, Point)>) {
+        let index = self.lines.len();
+        for cell in grid::cells(&line, self.cell_size) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+        let mut events = BTreeSet::from([line.p.clone(), line.q.clone()]);
+        for (other, point) in crossings {
+            self.events[other].insert(point.clone());
+            events.insert(point);
+        }
+        self.points.extend(events.iter().cloned());
+        self.events.push(events);
+        self.lines.push(line);
+    }
+}
+
+/// Whether one of the events lies closer than [`MIN_EVENT_GAP`] in x to the point.
+fn near(events: &BTreeSet<Point>, point: &Point) -> bool {
+    let from = Point {
+        x: point.x - MIN_EVENT_GAP,
+        y: f64::NEG_INFINITY,
+    };
+    let to = Point {
+        x: point.x + MIN_EVENT_GAP,
+        y: f64::INFINITY,
+    };
+    events.range(from..=to).next().is_some()
+}
+
+/// Whether the sweep orders both segments correctly after their rounded intersection `point`.
+///
+/// The sweep compares both segments [`SWAP_OFFSET`] right of the point, which gives the wrong order if the point of
+/// nearly parallel segments is off by about that much.
+fn separable(line: &Line, other: &Line, point: &Point) -> bool {
+    let slope = |line: &Line| (line.q.y - line.p.y) / (line.q.x - line.p.x);
+    // right of the intersection the steeper segment is above
+    let (upper, lower) = if slope(line) > slope(other) {
+        (line, other)
+    } else {
+        (other, line)
+    };
+    let x = point.x + SWAP_OFFSET;
+    upper.y(x) > lower.y(x)
+}
+
+/// Collects `count` segments from `f`, which is called with the index of the next segment until it yields one the
+/// sweep can handle next to the segments before, see the module documentation.
+///
+/// `cell_size` buckets the segments to find the ones a new segment may cross, about the length of a segment.
+fn collect_valid(
+    count: usize,
+    cell_size: f64,
+    mut f: impl FnMut(usize) -> Line,
+) -> Result<Vec<Line>, String> {
+    let mut accepted = Accepted::new(cell_size);
+    while accepted.lines.len() < count {
+        let index = accepted.lines.len();
+        let (line, crossings) = (0..MAX_ATTEMPTS)
+            .find_map(|_| {
+                let line = f(index);
+                accepted.crossings(&line).map(|crossings| (line, crossings))
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Could not place segment {} of {} in {} attempts, try fewer segments or a larger size",
+                    index + 1,
+                    count,
+                    MAX_ATTEMPTS
+                )
+            })?;
+        accepted.push(line, crossings);
+    }
+    Ok(accepted.lines)
+}
+
+/// Segment of the given length through `center`.
+fn centered(center: &Point, angle: f64, length: f64) -> Line {
+    let dx = 0.5 * length * angle.cos();
+    let dy = 0.5 * length * angle.sin();
+    Line {
+        p: Point {
+            x: center.x - dx,
+            y: center.y - dy,
+        },
+        q: Point {
+            x: center.x + dx,
+            y: center.y + dy,
+        },
+    }
+}
+
+/// Segment starting at `p`.
+fn starting_at(p: Point, angle: f64, length: f64) -> Line {
+    let q = Point {
+        x: p.x + length * angle.cos(),
+        y: p.y + length * angle.sin(),
+    };
+    Line { p, q }
+}
+
+/// Generates `options.count` segments of the chosen distribution.
+///
+/// Fails for a size or length which is not positive, or if the segments cannot be placed, because too many are
+/// squeezed into a too small area.
+pub fn generate(options: &GenerateOptions) -> Result<Vec<Line>, String> {
+    let mut rng = Rng::new(options.seed);
+    let count = options.count;
+    let size = options.size;
+    let max_length = options.max_length;
+    if !(size > 0.0 && size.is_finite() && max_length > 0.0 && max_length.is_finite()) {
+        return Err(format!(
+            "The size {} and the length {} must be positive",
+            size, max_length
+        ));
+    }
+
+    match options.distribution {
+        Distribution::Uniform => collect_valid(count, max_length, |_| {
+            let length = max_length * (1.0 - rng.next_f64());
+            let angle = rng.range(0.0, 2.0 * PI);
+            let p = Point {
+                x: rng.range(0.0, size),
+                y: rng.range(0.0, size),
+            };
+            starting_at(p, angle, length)
+        }),
+        Distribution::Clustered => {
+            let clusters = ((count as f64).sqrt() / 4.0).ceil().max(1.0) as usize;
+            let sigma = size / (4.0 * (clusters as f64).sqrt());
+            let centers: Vec<_> = (0..clusters)
+                .map(|_| Point {
+                    x: rng.range(0.0, size),
+                    y: rng.range(0.0, size),
+                })
+                .collect();
+            collect_valid(count, max_length, |_| {
+                let center = &centers[rng.next_u64() as usize % clusters];
+                let p = Point {
+                    x: center.x + sigma * rng.normal(),
+                    y: center.y + sigma * rng.normal(),
+                };
+                let length = max_length * (1.0 - rng.next_f64());
+                starting_at(p, rng.range(0.0, 2.0 * PI), length)
+            })
+        }
+        Distribution::Grid => {
+            let rows = count / 2;
+            let columns = count - rows;
+            // the segments span the whole area, every one is a candidate for every other one
+            collect_valid(count, size, |index| {
+                // jitter the spacing and the tilt, so no three segments meet in one point
+                let (slot, slots) = if index < rows {
+                    (index, rows)
+                } else {
+                    (index - rows, columns)
+                };
+                let spacing = size / slots as f64;
+                let offset = (slot as f64 + rng.range(0.25, 0.75)) * spacing;
+                let tilt = rng.range(-0.2, 0.2) * spacing;
+                let from = rng.range(0.0, 0.01) * size;
+                let to = size - rng.range(0.0, 0.01) * size;
+                if index < rows {
+                    Line {
+                        p: Point { x: from, y: offset },
+                        q: Point {
+                            x: to,
+                            y: offset + tilt,
+                        },
+                    }
+                } else {
+                    Line {
+                        p: Point { x: offset, y: from },
+                        q: Point {
+                            x: offset + tilt,
+                            y: to,
+                        },
+                    }
+                }
+            })
+        }
+        Distribution::Bundles => {
+            let bundles: Vec<_> = (0..count.div_ceil(GROUP_SIZE))
+                .map(|_| {
+                    let center = Point {
+                        x: rng.range(0.0, size),
+                        y: rng.range(0.0, size),
+                    };
+                    (center, rng.range(-FRAC_PI_3, FRAC_PI_3))
+                })
+                .collect();
+            collect_valid(count, max_length, |index| {
+                let (center, angle) = &bundles[index / GROUP_SIZE];
+                let along = rng.range(-0.25, 0.25) * max_length;
+                let across = rng.range(-0.02, 0.02) * max_length;
+                let center = Point {
+                    x: center.x + along * angle.cos() - across * angle.sin(),
+                    y: center.y + along * angle.sin() + across * angle.cos(),
+                };
+                let angle = angle + 1e-3 * rng.normal();
+                centered(&center, angle, max_length * rng.range(0.5, 1.0))
+            })
+        }
+        Distribution::Stars => {
+            let centers: Vec<_> = (0..count.div_ceil(GROUP_SIZE))
+                .map(|_| Point {
+                    x: rng.range(0.0, size),
+                    y: rng.range(0.0, size),
+                })
+                .collect();
+            collect_valid(count, max_length, |index| {
+                let center = &centers[index / GROUP_SIZE];
+                // miss the common center by a little, so every pair crosses in its own point
+                let jitter = 1e-3 * max_length;
+                let center = Point {
+                    x: center.x + rng.range(-jitter, jitter),
+                    y: center.y + rng.range(-jitter, jitter),
+                };
+                centered(
+                    &center,
+                    rng.range(0.0, PI),
+                    max_length * rng.range(0.5, 1.0),
+                )
+            })
+        }
+        Distribution::Adversarial => {
+            let spacing = size / count as f64;
+            collect_valid(count, size, |index| {
+                // left ends ascend and right ends descend, so the order of every pair is swapped
+                let left = (index as f64 + rng.range(0.1, 0.9)) * spacing;
+                let right = ((count - 1 - index) as f64 + rng.range(0.1, 0.9)) * spacing;
+                Line {
+                    p: Point {
+                        x: rng.range(0.0, 0.01) * size,
+                        y: left,
+                    },
+                    q: Point {
+                        x: size - rng.range(0.0, 0.01) * size,
+                        y: right,
+                    },
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{brute_force, event_queue::EventQueue};
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(42);
+        let values: Vec<_> = (0..1000).map(|_| rng.range(2.0, 3.0)).collect();
+        assert!(values.iter().all(|v| (2.0..3.0).contains(v)));
+
+        let mut same = Rng::new(42);
+        assert_eq!(same.range(2.0, 3.0), values[0]);
+        assert_ne!(Rng::new(43).range(2.0, 3.0), values[0]);
+
+        let mean = (0..10_000).map(|_| rng.normal()).sum::<f64>() / 10_000.0;
+        assert!(mean.abs() < 0.05);
+    }
+
+    #[test]
+    fn test_distributions() {
+        for distribution in [
+            Distribution::Uniform,
+            Distribution::Clustered,
+            Distribution::Grid,
+            Distribution::Bundles,
+            Distribution::Stars,
+            Distribution::Adversarial,
+        ] {
+            let options = GenerateOptions {
+                distribution,
+                count: 200,
+                ..Default::default()
+            };
+            let lines = generate(&options).unwrap();
+            assert_eq!(lines.len(), 200);
+            assert_eq!(lines, generate(&options).unwrap(), "{:?}", distribution);
+
+            let mut queue = EventQueue::default();
+            for line in lines.iter().cloned() {
+                queue.try_add_line(line).unwrap();
+            }
+            let found = queue.sweep();
+            let expected = brute_force::intersections(&lines);
+            assert_eq!(found.len(), expected.len(), "{:?}", distribution);
+        }
+    }
+
+    #[test]
+    fn test_uniform_lengths() {
+        let options = GenerateOptions::default();
+        assert!(generate(&options)
+            .unwrap()
+            .iter()
+            .all(|line| line.len() <= options.max_length + 1e-9));
+    }
+
+    #[test]
+    fn test_adversarial() {
+        let options = GenerateOptions {
+            distribution: Distribution::Adversarial,
+            count: 50,
+            ..Default::default()
+        };
+        let lines = generate(&options).unwrap();
+        assert_eq!(EventQueue::new(lines).sweep().len(), 50 * 49 / 2);
+    }
+
+    #[test]
+    fn test_rejected() {
+        // a vertical segment is drawn again and again
+        let vertical = |_| Line::from_str("1 0 1 1").unwrap();
+        assert!(collect_valid(1, 1.0, vertical).is_err());
+
+        // the second segment shares a point with the first one, the third one starts on it
+        let lines = ["0 0 2 2", "2 2 3 0", "1 1 3 5", "0 1 2 1.5"];
+        let mut next = lines.iter().map(|line| Line::from_str(line).unwrap());
+        let valid = collect_valid(2, 1.0, |_| next.next().unwrap()).unwrap();
+        assert_eq!(valid[1], Line::from_str("0 1 2 1.5").unwrap());
+
+        let options = GenerateOptions {
+            size: 0.0,
+            ..Default::default()
+        };
+        assert!(generate(&options).is_err());
+    }
+
+    #[test]
+    fn test_distribution_names() {
+        assert_eq!("gaussian".parse(), Ok(Distribution::Clustered));
+        assert_eq!("stars".parse(), Ok(Distribution::Stars));
+        assert!("normal".parse::<Distribution>().is_err());
+    }
+}
@@ -4,14 +4,20 @@ use std::{
     str::FromStr,
 };
 
+use crate::float;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
-fn round(x: f64, decimals: u32) -> f64 {
-    let y = 10i32.pow(decimals) as f64;
+/// Rounds to the given number of decimals, values which would overflow are returned as they are.
+pub fn round(x: f64, decimals: u32) -> f64 {
+    let y = 10f64.powi(decimals as i32);
+    if !(x * y).is_finite() {
+        return x;
+    }
     (x * y).round() / y
 }
 
@@ -52,45 +58,156 @@ impl PartialOrd for Point {
     }
 }
 
-impl Point {
-    fn from_str(x: &str, y: &str) -> Result<Point, ParseFloatError> {
-        let p = Point {
-            x: x.parse()?,
-            y: y.parse()?,
-        };
-
-        Ok(p)
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Ord)]
 pub struct Line {
     pub p: Point,
     pub q: Point,
 }
 
+impl Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.p, self.q)
+    }
+}
+
 impl Eq for Line {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParseLineError {
-    ParseFloat(ParseFloatError),
-    NotFourElements,
+    /// The value starting at the 1-based character `column` is not a number.
+    ParseFloat {
+        column: usize,
+        error: ParseFloatError,
+    },
+    /// The line does not consist of exactly four values, `column` points at the first extra value or the end of the line.
+    NotFourElements { found: usize, column: usize },
+    /// Same as `NotFourElements`, for a point `x y`.
+    NotTwoElements { found: usize, column: usize },
+    /// Same as `NotFourElements`, for an arc `cx cy r start end`.
+    NotFiveElements { found: usize, column: usize },
+    /// A polyline needs an even number of at least 4 values.
+    NotPolyline { found: usize, column: usize },
+    /// The value starting at `column` is a number, but not allowed there.
+    InvalidValue { column: usize, reason: &'static str },
 }
 
-impl FromStr for Line {
+impl ParseLineError {
+    pub fn column(&self) -> usize {
+        match self {
+            ParseLineError::ParseFloat { column, .. } => *column,
+            ParseLineError::NotFourElements { column, .. } => *column,
+            ParseLineError::NotTwoElements { column, .. } => *column,
+            ParseLineError::NotFiveElements { column, .. } => *column,
+            ParseLineError::NotPolyline { column, .. } => *column,
+            ParseLineError::InvalidValue { column, .. } => *column,
+        }
+    }
+}
+
+impl Display for ParseLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLineError::ParseFloat { error, .. } => write!(f, "invalid number: {}", error),
+            ParseLineError::NotFourElements { found, .. } => {
+                write!(f, "expected 4 values \"x1 y1 x2 y2\", found {}", found)
+            }
+            ParseLineError::NotTwoElements { found, .. } => {
+                write!(f, "expected 2 values \"x y\", found {}", found)
+            }
+            ParseLineError::NotFiveElements { found, .. } => {
+                write!(
+                    f,
+                    "expected 5 values \"cx cy r start end\", found {}",
+                    found
+                )
+            }
+            ParseLineError::NotPolyline { found, .. } => write!(
+                f,
+                "expected an even number of at least 4 values \"x1 y1 x2 y2 ...\", found {}",
+                found
+            ),
+            ParseLineError::InvalidValue { reason, .. } => write!(f, "invalid value: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseLineError {}
+
+/// 1-based character column of `value`, which must be a slice of `s`.
+pub(crate) fn column(s: &str, value: &str) -> usize {
+    s[..value.as_ptr() as usize - s.as_ptr() as usize]
+        .chars()
+        .count()
+        + 1
+}
+
+/// Parses one number of the line `s`, NaN and infinity would break the order of the sweep and are rejected.
+pub(crate) fn parse_value(s: &str, split: &str) -> Result<f64, ParseLineError> {
+    let value = float::parse(split).map_err(|error| ParseLineError::ParseFloat {
+        column: column(s, split),
+        error,
+    })?;
+    if !value.is_finite() {
+        return Err(ParseLineError::InvalidValue {
+            column: column(s, split),
+            reason: "values must be finite",
+        });
+    }
+    Ok(value)
+}
+
+/// Parses exactly `N` finite numbers separated by any whitespace.
+pub(crate) fn parse_values<const N: usize>(
+    s: &str,
+    wrong_count: fn(usize, usize) -> ParseLineError,
+) -> Result<[f64; N], ParseLineError> {
+    let column = |value: &str| column(s, value);
+    let splits: Vec<_> = s.split_whitespace().collect();
+
+    if splits.len() != N {
+        let column = match splits.get(N) {
+            Some(extra) => column(extra),
+            None => s.trim_end().chars().count() + 1,
+        };
+        return Err(wrong_count(splits.len(), column));
+    }
+
+    let mut values = [0.0; N];
+    for (value, split) in values.iter_mut().zip(&splits) {
+        *value = parse_value(s, split)?;
+    }
+
+    Ok(values)
+}
+
+impl FromStr for Point {
     type Err = ParseLineError;
 
+    /// Parses `x y`, the inverse of the `Display` implementation.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splits: Vec<_> = s.split(' ').collect();
+        let [x, y] = parse_values(s, |found, column| ParseLineError::NotTwoElements {
+            found,
+            column,
+        })?;
 
-        if splits.len() != 4 {
-            return Err(ParseLineError::NotFourElements);
-        }
+        Ok(Point { x, y })
+    }
+}
+
+impl FromStr for Line {
+    type Err = ParseLineError;
 
-        let p = Point::from_str(splits[0], splits[1]).map_err(ParseLineError::ParseFloat)?;
-        let q = Point::from_str(splits[2], splits[3]).map_err(ParseLineError::ParseFloat)?;
+    /// Parses `x1 y1 x2 y2`, the values may be separated by any whitespace and may be hexadecimal floats.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x1, y1, x2, y2] = parse_values(s, |found, column| ParseLineError::NotFourElements {
+            found,
+            column,
+        })?;
 
-        let line = Line { p, q };
+        let line = Line {
+            p: Point { x: x1, y: y1 },
+            q: Point { x: x2, y: y2 },
+        };
 
         Ok(line)
     }
@@ -103,9 +220,17 @@ impl Line {
         f64::sqrt(dx * dx + dy * dy)
     }
 
+    /// Intersection point of both segments, computed independent of the order of the two segments.
     pub fn intersection(&self, other: &Line) -> Option<Point> {
-        let p1 = &self.p;
-        let p2 = &self.q;
+        // nearly parallel segments give slightly different points for the two orders,
+        // which would report the same intersection twice
+        let (line, other) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let p1 = &line.p;
+        let p2 = &line.q;
         let q1 = &other.p;
         let q2 = &other.q;
 
@@ -125,6 +250,11 @@ impl Line {
             panic!("Two colinear lines were detected: {:?}, {:?}", self, other);
         }
 
+        // the end point of the other segment lies on this segment, the ratio below would be NaN
+        if ccwq1 == 0.0 {
+            return Some(q1.clone());
+        }
+
         // Determine intersection point
         let r_ab = (ccwq2 / ccwq1).abs();
         let a = r_ab / (r_ab + 1.0);
@@ -134,6 +264,48 @@ impl Line {
         Some(Point { x: i_x, y: i_y })
     }
 
+    /// Whether both segments lie on one line, for which [`Line::intersection`] panics.
+    pub fn is_collinear(&self, other: &Line) -> bool {
+        ccw(&self.p, &self.q, &other.p) == 0.0
+            && ccw(&self.p, &self.q, &other.q) == 0.0
+            && ccw(&other.p, &other.q, &self.p) == 0.0
+            && ccw(&other.p, &other.q, &self.q) == 0.0
+    }
+
+    /// Where both segments intersect along each of them, and how they cross, see [`IntersectionParameters`].
+    ///
+    /// `None` if they do not intersect, panics for collinear segments like [`Line::intersection`].
+    pub fn intersection_parameters(&self, other: &Line) -> Option<IntersectionParameters> {
+        self.intersection(other)?;
+
+        // signed areas of the end points of one segment with the other one, their ratio is the position of the
+        // intersection, like in `intersection`
+        let ratio = |from: f64, to: f64| match from - to {
+            0.0 => 0.0,
+            delta => (from / delta).clamp(0.0, 1.0),
+        };
+        let ccwq1 = ccw(&self.p, &self.q, &other.p);
+        let ccwq2 = ccw(&self.p, &self.q, &other.q);
+        let ccwp1 = ccw(&other.p, &other.q, &self.p);
+        let ccwp2 = ccw(&other.p, &other.q, &self.q);
+
+        let (dx, dy) = (self.q.x - self.p.x, self.q.y - self.p.y);
+        let (other_dx, other_dy) = (other.q.x - other.p.x, other.q.y - other.p.y);
+        let cross = dx * other_dy - dy * other_dx;
+        let dot = dx * other_dx + dy * other_dy;
+
+        Some(IntersectionParameters {
+            t: ratio(ccwp1, ccwp2),
+            other_t: ratio(ccwq1, ccwq2),
+            angle: cross.abs().atan2(dot).to_degrees(),
+            orientation: if cross < 0.0 {
+                Orientation::LeftToRight
+            } else {
+                Orientation::RightToLeft
+            },
+        })
+    }
+
     pub fn y(&self, x: f64) -> f64 {
         // calculate the lines y value at a certain x value
         let m = (self.p.y - self.q.y) / (self.p.x - self.q.x);
@@ -142,6 +314,104 @@ impl Line {
     }
 }
 
+/// Side from which the other segment crosses a segment, looking along the segment from `p` to `q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Orientation::LeftToRight => write!(f, "left-to-right"),
+            Orientation::RightToLeft => write!(f, "right-to-left"),
+        }
+    }
+}
+
+/// Position of an intersection along two segments and the way they cross, see [`Line::intersection_parameters`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntersectionParameters {
+    /// Position on the first segment, from `0` at `p` to `1` at `q`
+    pub t: f64,
+    /// Position on the other segment
+    pub other_t: f64,
+    /// Angle between the directions of both segments in degrees, from `0` to `180`
+    pub angle: f64,
+    /// How the other segment crosses the first one
+    pub orientation: Orientation,
+}
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn of_lines<'a>(lines: impl IntoIterator<Item = &'a Line>) -> Option<Self> {
+        Self::of_points(lines.into_iter().flat_map(|line| [&line.p, &line.q]))
+    }
+
+    pub fn of_points<'a>(points: impl IntoIterator<Item = &'a Point>) -> Option<Self> {
+        let mut bounds: Option<Bounds> = None;
+        for point in points {
+            match &mut bounds {
+                Some(b) => b.extend(point),
+                None => {
+                    bounds = Some(Bounds {
+                        min: point.clone(),
+                        max: point.clone(),
+                    })
+                }
+            }
+        }
+        bounds
+    }
+
+    pub fn extend(&mut self, point: &Point) {
+        self.min.x = self.min.x.min(point.x);
+        self.min.y = self.min.y.min(point.y);
+        self.max.x = self.max.x.max(point.x);
+        self.max.y = self.max.y.max(point.y);
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    /// The bounds grown by `fraction` of the [`extent`](Self::extent) on every side.
+    pub fn padded(&self, fraction: f64) -> Bounds {
+        let margin = fraction * self.extent();
+        Bounds {
+            min: Point {
+                x: self.min.x - margin,
+                y: self.min.y - margin,
+            },
+            max: Point {
+                x: self.max.x + margin,
+                y: self.max.y + margin,
+            },
+        }
+    }
+
+    /// Length of the longer side, never 0 so it can be used to scale marker sizes.
+    pub fn extent(&self) -> f64 {
+        let extent = self.width().max(self.height());
+        if extent > 0.0 {
+            extent
+        } else {
+            1.0
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -158,6 +428,126 @@ mod test {
         assert_eq!(line.y(1.), 1.);
     }
 
+    #[test]
+    fn test_intersection_symmetric() {
+        let line = Line::from_str("0 0.66 10 0.38").unwrap();
+        let other = Line::from_str("0.08 0.61 9.06 0.42").unwrap();
+
+        let point = line.intersection(&other).unwrap();
+        assert_eq!(other.intersection(&line), Some(point.clone()));
+        let expected = Point {
+            x: 7.060546875,
+            y: 0.462304687,
+        };
+        assert_eq!(point.round(9), expected);
+    }
+
+    #[test]
+    fn test_parse_whitespace() {
+        let line = Line::from_str("\t0  1\t2 3 \r").unwrap();
+        assert_eq!(line.p, Point { x: 0.0, y: 1.0 });
+        assert_eq!(line.q, Point { x: 2.0, y: 3.0 });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Line::from_str("0 1 2").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseLineError::NotFourElements { found: 3, .. }
+        ));
+        assert_eq!(err.column(), 6);
+
+        let err = Line::from_str("0 1 2 3 4").unwrap_err();
+        assert_eq!(
+            err,
+            ParseLineError::NotFourElements {
+                found: 5,
+                column: 9
+            }
+        );
+
+        let err = Line::from_str("0  1 x 3").unwrap_err();
+        assert!(matches!(err, ParseLineError::ParseFloat { column: 6, .. }));
+
+        let err = Line::from_str("NaN 0 1 1").unwrap_err();
+        assert_eq!(
+            err,
+            ParseLineError::InvalidValue {
+                column: 1,
+                reason: "values must be finite"
+            }
+        );
+        assert_eq!(Line::from_str("0 0 inf 2").unwrap_err().column(), 5);
+        assert_eq!(Point::from_str("0 -infinity").unwrap_err().column(), 3);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let lines = [
+            Line::from_str("0 1 2 3").unwrap(),
+            Line::from_str("-1 5 4 2").unwrap(),
+        ];
+        let bounds = Bounds::of_lines(&lines).unwrap();
+
+        assert_eq!(bounds.min, Point { x: -1.0, y: 1.0 });
+        assert_eq!(bounds.max, Point { x: 4.0, y: 5.0 });
+        assert_eq!(bounds.extent(), 5.0);
+        assert_eq!(Bounds::of_lines(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_point() {
+        let point = Point {
+            x: 0.1 + 0.2,
+            y: -3.0,
+        };
+        assert_eq!(Point::from_str(&point.to_string()), Ok(point));
+        assert_eq!(
+            Point::from_str("1 2 3"),
+            Err(ParseLineError::NotTwoElements {
+                found: 3,
+                column: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_intersection_at_end_point() {
+        let line = Line::from_str("1 0 1 4").unwrap();
+        let touching = Line::from_str("1 2 3 2").unwrap();
+        let expected = Some(Point { x: 1.0, y: 2.0 });
+        assert_eq!(line.intersection(&touching), expected);
+        assert_eq!(touching.intersection(&line), expected);
+    }
+
+    #[test]
+    fn test_intersection_parameters() {
+        let line = Line::from_str("0 0 4 0").unwrap();
+        let other = Line::from_str("1 1 1 -3").unwrap();
+        let parameters = line.intersection_parameters(&other).unwrap();
+        assert_eq!(parameters.t, 0.25);
+        assert_eq!(parameters.other_t, 0.25);
+        assert_eq!(parameters.angle, 90.0);
+        assert_eq!(parameters.orientation, Orientation::LeftToRight);
+
+        let diagonal = Line::from_str("4 -1 2 1").unwrap();
+        let parameters = diagonal.intersection_parameters(&line).unwrap();
+        assert_eq!((parameters.t, parameters.other_t), (0.5, 0.75));
+        assert!((parameters.angle - 135.0).abs() < 1e-12);
+        assert_eq!(parameters.orientation, Orientation::LeftToRight);
+        assert_eq!(parameters.orientation.to_string(), "left-to-right");
+
+        // touching in an end point
+        let touching = Line::from_str("4 0 5 1").unwrap();
+        let parameters = line.intersection_parameters(&touching).unwrap();
+        assert_eq!((parameters.t, parameters.other_t), (1.0, 0.0));
+        assert_eq!(parameters.orientation, Orientation::RightToLeft);
+        assert!(line
+            .intersection_parameters(&Line::from_str("0 1 4 2").unwrap())
+            .is_none());
+    }
+
     // test for line with no m
     #[test]
     fn test_y_no_m() {
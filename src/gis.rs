@@ -0,0 +1,442 @@
+use std::io::{BufRead, Read};
+
+use serde_json::Value;
+
+use crate::{
+    geometry::{Line, Point},
+    input::ReadError,
+    polyline::Polyline,
+};
+
+/// A feature with its line strings and polygon rings as polylines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    pub id: String,
+    pub paths: Vec<Polyline>,
+}
+
+impl Feature {
+    /// Skips repeated vertices, and paths left with less than two points.
+    fn new(id: String, paths: Vec<Vec<Point>>) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|mut points| {
+                points.dedup();
+                Polyline { points }
+            })
+            .filter(|path| path.points.len() > 1)
+            .collect();
+        Feature { id, paths }
+    }
+
+    /// The segments of all paths, in the order of the coordinates.
+    pub fn segments(&self) -> impl Iterator<Item = Line> + '_ {
+        self.paths.iter().flat_map(Polyline::segments)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            c if c.is_whitespace() => {}
+            _ => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || "(),".contains(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &text[start..end];
+                tokens.push(match word.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => Token::Word(word.to_ascii_uppercase()),
+                });
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    tokens
+}
+
+/// Recursive descent parser over the tokens of a single WKT geometry.
+struct WktParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl WktParser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(format!("expected {:?}, found {:?}", expected, token)),
+        }
+    }
+
+    /// Parses a comma separated list in parentheses, `EMPTY` is an empty list.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        if self.peek() == Some(&Token::Word("EMPTY".to_string())) {
+            self.next();
+            return Ok(Vec::new());
+        }
+
+        self.expect(Token::Open)?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(&Token::Comma) {
+            self.next();
+            items.push(item(self)?);
+        }
+        self.expect(Token::Close)?;
+        Ok(items)
+    }
+
+    /// `x y` with optional z and m values, which are ignored.
+    fn coordinate(&mut self) -> Result<Point, String> {
+        let mut values = Vec::new();
+        while let Some(Token::Number(value)) = self.peek() {
+            values.push(*value);
+            self.next();
+        }
+        if values.len() < 2 || values.len() > 4 {
+            return Err(format!(
+                "expected 2 to 4 coordinates, found {}",
+                values.len()
+            ));
+        }
+        Ok(Point {
+            x: values[0],
+            y: values[1],
+        })
+    }
+
+    fn path(&mut self) -> Result<Vec<Point>, String> {
+        self.list(Self::coordinate)
+    }
+
+    fn paths(&mut self) -> Result<Vec<Vec<Point>>, String> {
+        self.list(Self::path)
+    }
+
+    /// All paths of the next geometry, points become empty paths.
+    fn geometry(&mut self) -> Result<Vec<Vec<Point>>, String> {
+        let Some(Token::Word(kind)) = self.next() else {
+            return Err("expected a geometry type".to_string());
+        };
+        while let Some(Token::Word(dimension)) = self.peek() {
+            if !["Z", "M", "ZM"].contains(&dimension.as_str()) {
+                break;
+            }
+            self.next();
+        }
+
+        match kind.as_str() {
+            "POINT" => self.path().map(|_| Vec::new()),
+            "MULTIPOINT" => self.list(|p| p.coordinate_or_point()).map(|_| Vec::new()),
+            "LINESTRING" => Ok(vec![self.path()?]),
+            "MULTILINESTRING" | "POLYGON" => self.paths(),
+            "MULTIPOLYGON" => Ok(self.list(Self::paths)?.into_iter().flatten().collect()),
+            "GEOMETRYCOLLECTION" => Ok(self.list(Self::geometry)?.into_iter().flatten().collect()),
+            _ => Err(format!("unsupported geometry type {}", kind)),
+        }
+    }
+
+    /// Points of a MULTIPOINT may or may not be wrapped in parentheses.
+    fn coordinate_or_point(&mut self) -> Result<Point, String> {
+        if self.peek() == Some(&Token::Open) {
+            self.next();
+            let point = self.coordinate()?;
+            self.expect(Token::Close)?;
+            Ok(point)
+        } else {
+            self.coordinate()
+        }
+    }
+}
+
+const WKT_TYPES: [&str; 7] = [
+    "POINT",
+    "MULTIPOINT",
+    "LINESTRING",
+    "MULTILINESTRING",
+    "POLYGON",
+    "MULTIPOLYGON",
+    "GEOMETRYCOLLECTION",
+];
+
+/// Splits a line into the optional feature id in front of the geometry and the geometry itself.
+fn split_id(text: &str) -> (&str, &str) {
+    let is_separator = |c: char| c.is_whitespace() || c == ';' || c == ',';
+
+    // the geometry starts at the first word which is a geometry type
+    let start = text
+        .char_indices()
+        .filter(|&(i, _)| i == 0 || text[..i].ends_with(is_separator))
+        .map(|(i, _)| i)
+        .find(|&i| {
+            let word = text[i..]
+                .split(|c: char| is_separator(c) || c == '(')
+                .next()
+                .unwrap_or_default();
+            WKT_TYPES.contains(&word.to_ascii_uppercase().as_str())
+        })
+        .unwrap_or(0);
+
+    (text[..start].trim_matches(is_separator), &text[start..])
+}
+
+/// Parses one WKT geometry into its paths.
+pub fn parse_wkt(text: &str) -> Result<Vec<Vec<Point>>, String> {
+    let mut parser = WktParser {
+        tokens: tokenize(text),
+        position: 0,
+    };
+    let paths = parser.geometry()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {:?} after the geometry", token));
+    }
+    Ok(paths)
+}
+
+/// Reads one WKT geometry per line, e.g. `LINESTRING (0 0, 1 1, 2 0)`.
+///
+/// A line may start with a feature id in front of the geometry, separated by whitespace, `;` or `,`.
+/// Without an id, the 1-based line number is used. Blank lines and lines starting with `#` are skipped.
+pub fn read_wkt<R: BufRead>(name: &str, reader: R) -> Result<Vec<Feature>, ReadError> {
+    let mut features = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let text = line.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let (id, geometry) = split_id(text);
+        let paths = parse_wkt(geometry).map_err(|message| {
+            ReadError::Format(format!("{}:{}: {}", name, number + 1, message))
+        })?;
+        let id = if id.is_empty() {
+            (number + 1).to_string()
+        } else {
+            id.to_string()
+        };
+        features.push(Feature::new(id, paths));
+    }
+
+    Ok(features)
+}
+
+fn geojson_point(value: &Value) -> Result<Point, String> {
+    let coordinates = value.as_array().ok_or("expected a position")?;
+    let x = coordinates.first().and_then(Value::as_f64);
+    let y = coordinates.get(1).and_then(Value::as_f64);
+    match (x, y) {
+        (Some(x), Some(y)) => Ok(Point { x, y }),
+        _ => Err(format!("invalid position {}", value)),
+    }
+}
+
+fn geojson_path(value: &Value) -> Result<Vec<Point>, String> {
+    value
+        .as_array()
+        .ok_or("expected an array of positions")?
+        .iter()
+        .map(geojson_point)
+        .collect()
+}
+
+fn geojson_paths(value: &Value) -> Result<Vec<Vec<Point>>, String> {
+    value
+        .as_array()
+        .ok_or("expected an array of paths")?
+        .iter()
+        .map(geojson_path)
+        .collect()
+}
+
+fn geojson_geometry(geometry: &Value) -> Result<Vec<Vec<Point>>, String> {
+    if geometry.is_null() {
+        return Ok(Vec::new());
+    }
+    let kind = geometry["type"].as_str().ok_or("geometry without type")?;
+    let coordinates = &geometry["coordinates"];
+
+    match kind {
+        "Point" | "MultiPoint" => Ok(Vec::new()),
+        "LineString" => Ok(vec![geojson_path(coordinates)?]),
+        "MultiLineString" | "Polygon" => geojson_paths(coordinates),
+        "MultiPolygon" => {
+            let polygons = coordinates
+                .as_array()
+                .ok_or("expected an array of polygons")?;
+            let mut paths = Vec::new();
+            for polygon in polygons {
+                paths.extend(geojson_paths(polygon)?);
+            }
+            Ok(paths)
+        }
+        "GeometryCollection" => {
+            let geometries = geometry["geometries"]
+                .as_array()
+                .ok_or("expected an array of geometries")?;
+            let mut paths = Vec::new();
+            for geometry in geometries {
+                paths.extend(geojson_geometry(geometry)?);
+            }
+            Ok(paths)
+        }
+        _ => Err(format!("unsupported geometry type {}", kind)),
+    }
+}
+
+/// The feature `id`, or the `id` property, or the position of the feature in the collection.
+fn feature_id(feature: &Value, position: usize) -> String {
+    let id = match &feature["id"] {
+        Value::Null => &feature["properties"]["id"],
+        id => id,
+    };
+    match id {
+        Value::String(id) => id.clone(),
+        Value::Number(id) => id.to_string(),
+        _ => position.to_string(),
+    }
+}
+
+/// Reads a GeoJSON `FeatureCollection`, a single `Feature` or a bare geometry.
+pub fn read_geojson<R: Read>(name: &str, reader: R) -> Result<Vec<Feature>, ReadError> {
+    let format_error = |message: String| ReadError::Format(format!("{}: {}", name, message));
+
+    let root: Value =
+        serde_json::from_reader(reader).map_err(|err| format_error(err.to_string()))?;
+    let features = match root["type"].as_str() {
+        Some("FeatureCollection") => root["features"]
+            .as_array()
+            .ok_or_else(|| format_error("features is not an array".to_string()))?
+            .iter()
+            .collect(),
+        Some("Feature") => vec![&root],
+        _ => Vec::new(),
+    };
+
+    if features.is_empty() && root["type"].as_str() != Some("FeatureCollection") {
+        let paths = geojson_geometry(&root).map_err(format_error)?;
+        return Ok(vec![Feature::new("0".to_string(), paths)]);
+    }
+    features
+        .into_iter()
+        .enumerate()
+        .map(|(position, feature)| {
+            let id = feature_id(feature, position);
+            let paths = geojson_geometry(&feature["geometry"])
+                .map_err(|message| format_error(format!("feature {}: {}", id, message)))?;
+            Ok(Feature::new(id, paths))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_wkt() {
+        let paths = parse_wkt("LINESTRING (0 0, 1 1, 2 0)").unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].len(), 3);
+
+        let paths = parse_wkt("multilinestring((0 0,1 1),(2 2, 3 3))").unwrap();
+        assert_eq!(paths.len(), 2);
+
+        let paths = parse_wkt("MULTIPOLYGON (((0 0, 4 0, 4 4, 0 0)), ((5 5, 6 5, 6 6, 5 5), (5.2 5.1, 5.8 5.1, 5.8 5.5, 5.2 5.1)))").unwrap();
+        assert_eq!(paths.len(), 3);
+
+        let paths = parse_wkt("LINESTRING Z (0 0 1, 1 1 2)").unwrap();
+        assert_eq!(paths[0][1], Point { x: 1.0, y: 1.0 });
+
+        assert!(parse_wkt("POINT (1 2)").unwrap().is_empty());
+        assert!(parse_wkt("LINESTRING EMPTY").unwrap()[0].is_empty());
+        assert!(parse_wkt("LINESTRING (0 0, 1)").is_err());
+        assert!(parse_wkt("LINESTRING (0 0, 1 1").is_err());
+        assert!(parse_wkt("CIRCLE (0 0, 1 1)").is_err());
+    }
+
+    #[test]
+    fn test_read_wkt() {
+        let input = "waypoint-1 LINESTRING (0 0, 1 1, 2 0)\n\n# comment\n7;POLYGON ((0 2, 2 2, 1 3, 0 2))\nLINESTRING (5 5, 5 5, 6 7)\n";
+        let features = read_wkt("test.wkt", input.as_bytes()).unwrap();
+
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0].id, "waypoint-1");
+        assert_eq!(features[0].segments().count(), 2);
+        assert_eq!(features[1].id, "7");
+        assert!(features[1].paths[0].is_closed());
+        // repeated vertex is skipped, feature id falls back to the line number
+        assert_eq!(features[2].id, "5");
+        assert_eq!(features[2].paths[0].points.len(), 2);
+
+        let error = read_wkt(
+            "test.wkt",
+            "LINESTRING (0 0, 1 1)\nLINESTRING (0 0,".as_bytes(),
+        );
+        assert!(error.unwrap_err().to_string().starts_with("test.wkt:2:"));
+    }
+
+    #[test]
+    fn test_read_geojson() {
+        let input = r#"{
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "id": "a", "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1], [2, 0]]}},
+                {"type": "Feature", "properties": {"id": 42}, "geometry": {"type": "MultiPolygon", "coordinates": [[[[0, 2], [2, 2], [1, 3], [0, 2]]]]}},
+                {"type": "Feature", "properties": {}, "geometry": {"type": "Point", "coordinates": [5, 5]}},
+                {"type": "Feature", "geometry": {"type": "LineString", "coordinates": [[5, 5], [6, 7]]}}
+            ]
+        }"#;
+        let features = read_geojson("test.geojson", input.as_bytes()).unwrap();
+
+        assert_eq!(features.len(), 4);
+        assert_eq!(features[0].id, "a");
+        assert_eq!(features[1].id, "42");
+        assert_eq!(features[1].segments().count(), 3);
+        assert!(features[2].paths.is_empty());
+        assert_eq!(features[3].id, "3");
+
+        let geometry = r#"{"type": "LineString", "coordinates": [[0, 0], [1, 1]]}"#;
+        let features = read_geojson("g", geometry.as_bytes()).unwrap();
+        assert_eq!(features[0].segments().count(), 1);
+
+        let invalid = r#"{"type": "LineString", "coordinates": [[0, 0], [1]]}"#;
+        assert!(read_geojson("g", invalid.as_bytes()).is_err());
+    }
+}
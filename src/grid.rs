@@ -0,0 +1,167 @@
+//! Uniform grid backend, which only tests segments sharing a grid cell.
+//!
+//! Faster than the sweep for many short, evenly spread segments, but degrades to brute force
+//! for long segments covering many cells.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    arena::SegmentId,
+    geometry::{Bounds, Line, Point},
+};
+
+/// Grid cells the segment passes through, column by column.
+///
+/// Only the cells crossed by the segment are visited, so a long diagonal segment covers about `2 * length / cell_size`
+/// cells instead of every cell of its bounding box. The rows of each column are padded a little, so rounding errors
+/// never drop a cell the segment just touches.
+pub fn cells(line: &Line, cell_size: f64) -> impl Iterator<Item = (i64, i64)> {
+    let (left, right) = if line.p.x <= line.q.x {
+        (line.p.clone(), line.q.clone())
+    } else {
+        (line.q.clone(), line.p.clone())
+    };
+    let cell = move |value: f64| (value / cell_size).floor() as i64;
+    let pad = 1e-9 * cell_size.max(left.y.abs()).max(right.y.abs());
+    (cell(left.x)..=cell(right.x)).flat_map(move |column| {
+        let (from, to) = if left.x == right.x {
+            (left.y, right.y)
+        } else {
+            let y = |x: f64| left.y + (right.y - left.y) * ((x - left.x) / (right.x - left.x));
+            let from = (column as f64 * cell_size).max(left.x);
+            let to = ((column + 1) as f64 * cell_size).min(right.x);
+            (y(from), y(to))
+        };
+        let (low, high) = (from.min(to) - pad, from.max(to) + pad);
+        (cell(low)..=cell(high)).map(move |row| (column, row))
+    })
+}
+
+/// Longer side of the bounding box of the segment.
+pub fn extent(line: &Line) -> f64 {
+    (line.p.x - line.q.x).abs().max((line.p.y - line.q.y).abs())
+}
+
+/// Cell size for the segments: about the size of a segment, but at most about as many cells as segments.
+pub fn cell_size(lines: &[Line]) -> f64 {
+    let Some(bounds) = Bounds::of_lines(lines) else {
+        return 1.0;
+    };
+    let mean_extent = lines.iter().map(extent).sum::<f64>() / lines.len() as f64;
+    let spacing = (bounds.width() * bounds.height() / lines.len() as f64).sqrt();
+    let size = mean_extent.max(spacing);
+    if size > 0.0 && size.is_finite() {
+        size
+    } else {
+        bounds.extent()
+    }
+}
+
+/// Same result as [`EventQueue::sweep`](crate::event_queue::EventQueue::sweep), by testing every pair of segments sharing a cell.
+pub fn intersections(lines: &[Line]) -> BTreeSet<Point> {
+    intersections_with_cell_size(lines, cell_size(lines))
+}
+
+pub fn intersections_with_cell_size(lines: &[Line], cell_size: f64) -> BTreeSet<Point> {
+    // pairs sharing several cells are found in each of them, the set keeps one point
+    let mut intersections = BTreeSet::new();
+    pairs(lines, cell_size, |point, _, _| {
+        intersections.insert(point);
+    });
+    intersections
+}
+
+/// Like [`intersections`], with the ids of all segments through each point in ascending order, which are their
+/// positions in `lines`.
+pub fn crossings(lines: &[Line]) -> BTreeMap<Point, Vec<SegmentId>> {
+    let mut crossings: BTreeMap<Point, Vec<SegmentId>> = BTreeMap::new();
+    pairs(lines, cell_size(lines), |point, segment, other_segment| {
+        crossings
+            .entry(point)
+            .or_default()
+            .extend([SegmentId::new(segment), SegmentId::new(other_segment)]);
+    });
+    for segments in crossings.values_mut() {
+        segments.sort_unstable();
+        segments.dedup();
+    }
+    crossings
+}
+
+/// Calls `report` with the rounded point and the positions of both segments of every intersecting pair sharing a
+/// cell, once per shared cell.
+fn pairs(lines: &[Line], cell_size: f64, mut report: impl FnMut(Point, usize, usize)) {
+    let mut grid: HashMap<_, Vec<_>> = HashMap::new();
+    for (index, line) in lines.iter().enumerate() {
+        for cell in cells(line, cell_size) {
+            grid.entry(cell).or_default().push(index);
+        }
+    }
+
+    for segments in grid.values() {
+        for (position, &index) in segments.iter().enumerate() {
+            for &other_index in &segments[position + 1..] {
+                if let Some(point) = lines[index].intersection(&lines[other_index]) {
+                    report(point.round(9), index, other_index);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{brute_force, event_queue::EventQueue, input::read_file};
+
+    #[test]
+    fn test_same_as_sweep() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let expected = EventQueue::new(lines.clone()).sweep();
+        assert_eq!(intersections(&lines), expected);
+        // a single cell is brute force, tiny cells put long segments into many cells
+        assert_eq!(intersections_with_cell_size(&lines, 1e6), expected);
+        assert_eq!(intersections_with_cell_size(&lines, 0.5), expected);
+        assert_eq!(
+            intersections_with_cell_size(&lines, 1e6),
+            brute_force::intersections(&lines)
+        );
+    }
+
+    #[test]
+    fn test_crossings() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let expected = brute_force::crossings(&lines);
+        assert_eq!(crossings(&lines), expected);
+        assert!(expected.values().all(|segments| segments.len() >= 2));
+    }
+
+    #[test]
+    fn test_cells() {
+        let line = Line {
+            p: Point { x: 0.5, y: 2.5 },
+            q: Point { x: 2.5, y: 1.5 },
+        };
+        let cells: Vec<_> = cells(&line, 1.0).collect();
+        assert_eq!(cells, [(0, 2), (1, 1), (1, 2), (2, 1)]);
+        assert_eq!(extent(&line), 2.0);
+        assert_eq!(cell_size(&[]), 1.0);
+    }
+
+    #[test]
+    fn test_cells_diagonal() {
+        // only the cells along the diagonal, not the 10⁶ cells of its bounding box
+        let line = Line::from_str("0.5 0.25 1000.5 1000.25").unwrap();
+        let diagonal: Vec<_> = cells(&line, 1.0).collect();
+        assert_eq!(diagonal.len(), 2001);
+        assert!(diagonal
+            .windows(2)
+            .all(|pair| pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1));
+
+        let line = Line::from_str("2.5 0.5 2.5 3.5").unwrap();
+        let vertical: Vec<_> = cells(&line, 1.0).collect();
+        assert_eq!(vertical, [(2, 0), (2, 1), (2, 2), (2, 3)]);
+    }
+}
@@ -0,0 +1,376 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs,
+    io::{self, BufRead, BufReader},
+    str::FromStr,
+};
+
+use log::{info, warn};
+
+use crate::{
+    geometry::{Line, ParseLineError, Point},
+    polyline::Polyline,
+    primitive::Primitive,
+};
+
+/// Supported input formats, see [`InputFormat::from_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// One `x1 y1 x2 y2` segment per line
+    Segments,
+    /// One WKT geometry per line, see [`read_wkt`](crate::gis::read_wkt)
+    Wkt,
+    /// A GeoJSON document, see [`read_geojson`](crate::gis::read_geojson)
+    GeoJson,
+    /// Segments in the binary format, see [`read_segments`](crate::binary::read_segments)
+    Binary,
+    /// One `x1 y1 x2 y2 ...` polyline per line, see [`Polyline`]
+    Polylines,
+}
+
+impl InputFormat {
+    /// Picks the format by file extension: `.wkt`, `.geojson`, `.json`, `.bin` and `.polylines`; everything else is
+    /// read as segments.
+    pub fn from_path(path: &str) -> Self {
+        let extension = path
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("wkt") => InputFormat::Wkt,
+            Some("geojson") | Some("json") => InputFormat::GeoJson,
+            Some("bin") => InputFormat::Binary,
+            Some("polylines") => InputFormat::Polylines,
+            _ => InputFormat::Segments,
+        }
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "segments" => Ok(InputFormat::Segments),
+            "wkt" => Ok(InputFormat::Wkt),
+            "geojson" => Ok(InputFormat::GeoJson),
+            "binary" => Ok(InputFormat::Binary),
+            "polylines" => Ok(InputFormat::Polylines),
+            _ => Err(format!("Unknown input format: {}", s)),
+        }
+    }
+}
+
+/// Number of segments between two progress messages.
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// A line of the input which could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based character column
+    pub column: usize,
+    pub kind: ParseLineError,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.line, self.column, self.kind
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+    /// Invalid WKT, GeoJSON or binary input, the message includes the file and the position if known.
+    Format(String),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "{}", error),
+            ReadError::Parse(error) => write!(f, "{}", error),
+            ReadError::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(error: ParseError) -> Self {
+        ReadError::Parse(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadSummary {
+    pub segments: usize,
+    /// Invalid lines skipped with a warning, see [`SegmentReader::skip_invalid`].
+    pub skipped: usize,
+}
+
+/// Buffered reader for the `x1 y1 x2 y2` segment format, one segment per line.
+///
+/// Values may be separated by any whitespace, blank lines are ignored and `#` starts a comment until the end of the line.
+pub struct SegmentReader<R> {
+    reader: R,
+    name: String,
+    size: Option<u64>,
+    skip_invalid: bool,
+}
+
+impl SegmentReader<Box<dyn BufRead>> {
+    /// Opens a file for buffered reading, `-` reads from stdin.
+    pub fn open(path: &str) -> io::Result<Self> {
+        if path == "-" {
+            return Ok(Self::new("stdin", Box::new(io::stdin().lock())));
+        }
+
+        let file = fs::File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut reader = Self::new(path, Box::new(BufReader::new(file)) as Box<dyn BufRead>);
+        reader.size = Some(size);
+        Ok(reader)
+    }
+}
+
+impl<R: BufRead> SegmentReader<R> {
+    /// `name` is only used in error messages.
+    pub fn new(name: &str, reader: R) -> Self {
+        Self {
+            reader,
+            name: name.to_string(),
+            size: None,
+            skip_invalid: false,
+        }
+    }
+
+    /// The name of the input in error messages.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The underlying reader, for input in other formats.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Skip lines which cannot be parsed with a warning, instead of failing.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Hands every segment to `f` as soon as it is parsed, without keeping the input in memory.
+    ///
+    /// Logs the progress every million segments.
+    pub fn read(self, f: impl FnMut(Line)) -> Result<ReadSummary, ReadError> {
+        self.read_values(f)
+    }
+
+    /// Like [`SegmentReader::read`], but lines may also be rays or infinite lines, see [`Primitive`].
+    pub fn read_primitives(self, f: impl FnMut(Primitive)) -> Result<ReadSummary, ReadError> {
+        self.read_values(f)
+    }
+
+    /// Like [`SegmentReader::read`], for one point `x y` per line like the intersections written by the sweep.
+    pub fn read_points(self, f: impl FnMut(Point)) -> Result<ReadSummary, ReadError> {
+        self.read_values(f)
+    }
+
+    /// Like [`SegmentReader::read`], for one polyline per line.
+    pub fn read_polylines(self, f: impl FnMut(Polyline)) -> Result<ReadSummary, ReadError> {
+        self.read_values(f)
+    }
+
+    fn read_values<T: FromStr<Err = ParseLineError>>(
+        mut self,
+        mut f: impl FnMut(T),
+    ) -> Result<ReadSummary, ReadError> {
+        let mut buffer = String::new();
+        let mut bytes_read = 0u64;
+        let mut line_number = 0;
+        let mut summary = ReadSummary::default();
+
+        loop {
+            buffer.clear();
+            let bytes = self.reader.read_line(&mut buffer)?;
+            if bytes == 0 {
+                break;
+            }
+            bytes_read += bytes as u64;
+            line_number += 1;
+
+            let content = match buffer.find('#') {
+                Some(comment) => &buffer[..comment],
+                None => &buffer,
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+
+            match content.parse() {
+                Ok(line) => f(line),
+                Err(kind) => {
+                    let error = ParseError {
+                        file: self.name.clone(),
+                        line: line_number,
+                        column: kind.column(),
+                        kind,
+                    };
+                    if !self.skip_invalid {
+                        return Err(error.into());
+                    }
+                    warn!("Skipping {}", error);
+                    summary.skipped += 1;
+                    continue;
+                }
+            }
+            summary.segments += 1;
+
+            if summary.segments % PROGRESS_INTERVAL == 0 {
+                match self.size {
+                    Some(size) if size > 0 => info!(
+                        "Read {} segments ({:.0}%)",
+                        summary.segments,
+                        100.0 * bytes_read as f64 / size as f64
+                    ),
+                    _ => info!("Read {} segments", summary.segments),
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Reads all segments of a file into memory.
+pub fn read_file(path: &str) -> Result<Vec<Line>, ReadError> {
+    let mut lines = Vec::new();
+    SegmentReader::open(path)?.read(|line| lines.push(line))?;
+    Ok(lines)
+}
+
+/// Reads all polylines of a file into memory.
+pub fn read_polyline_file(path: &str) -> Result<Vec<Polyline>, ReadError> {
+    let mut polylines = Vec::new();
+    SegmentReader::open(path)?.read_polylines(|polyline| polylines.push(polyline))?;
+    Ok(polylines)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_segments() {
+        let input = "0 1 2 3\r\n4\t5  6 7 \n";
+        let mut lines = Vec::new();
+        let summary = SegmentReader::new("test", input.as_bytes())
+            .read(|line| lines.push(line))
+            .unwrap();
+
+        assert_eq!(summary.segments, 2);
+        assert_eq!(lines[0].p, Point { x: 0.0, y: 1.0 });
+        assert_eq!(lines[1].q, Point { x: 6.0, y: 7.0 });
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines() {
+        let input = "# header\n\n0 1 2 3 # first\n   \n# 4 5 6 7\n";
+        let mut lines = Vec::new();
+        let summary = SegmentReader::new("test", input.as_bytes())
+            .read(|line| lines.push(line))
+            .unwrap();
+
+        assert_eq!(summary.segments, 1);
+        assert_eq!(lines.len(), 1);
+    }
+
+    #[test]
+    fn test_error_position() {
+        let input = "0 1 2 3\n\n4 5 x 7\n";
+        let error = SegmentReader::new("test.dat", input.as_bytes())
+            .read(|_| {})
+            .unwrap_err();
+
+        let ReadError::Parse(error) = error else {
+            panic!("Expected a parse error, got {:?}", error);
+        };
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 5);
+        assert!(error
+            .to_string()
+            .starts_with("test.dat:3:5: invalid number"));
+    }
+
+    #[test]
+    fn test_input_format() {
+        assert_eq!(
+            InputFormat::from_path("data/s_1000_10.dat"),
+            InputFormat::Segments
+        );
+        assert_eq!(InputFormat::from_path("roads.WKT"), InputFormat::Wkt);
+        assert_eq!(
+            InputFormat::from_path("a.b/roads.geojson"),
+            InputFormat::GeoJson
+        );
+        assert_eq!(InputFormat::from_path("roads.json"), InputFormat::GeoJson);
+        assert_eq!(InputFormat::from_path("s_1000_10.bin"), InputFormat::Binary);
+        assert_eq!(
+            InputFormat::from_path("roads.polylines"),
+            InputFormat::Polylines
+        );
+        assert_eq!("wkt".parse(), Ok(InputFormat::Wkt));
+        assert!("shp".parse::<InputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_read_primitives() {
+        let input = "0 1 2 3\nray 4 5 6 7\nline 0 0 1 x\n";
+        let mut primitives = Vec::new();
+        let error = SegmentReader::new("test", input.as_bytes())
+            .read_primitives(|primitive| primitives.push(primitive))
+            .unwrap_err();
+
+        assert!(error.to_string().starts_with("test:3:12: invalid number"));
+        assert!(primitives[0].is_segment());
+        assert_eq!(primitives[1].to_string(), "ray 4 5 6 7");
+    }
+
+    #[test]
+    fn test_skip_invalid() {
+        let input = "0 1 2 3\n4 5 6\nNaN 0 1 1\n0 0 inf 2\n8 9 10 11\n";
+        let mut lines = Vec::new();
+        let summary = SegmentReader::new("test", input.as_bytes())
+            .skip_invalid(true)
+            .read(|line| lines.push(line))
+            .unwrap();
+
+        assert_eq!(summary.segments, 2);
+        assert_eq!(summary.skipped, 3);
+        assert_eq!(lines.len(), 2);
+
+        let error = SegmentReader::new("test", "0 1 2 3\n0 0 inf 2\n".as_bytes())
+            .read(|_| {})
+            .unwrap_err();
+        assert!(error.to_string().starts_with("test:2:5: "));
+    }
+}
@@ -0,0 +1,24 @@
+pub mod algorithm;
+pub mod animation;
+pub mod arc;
+pub mod arena;
+pub mod binary;
+pub mod brute_force;
+pub mod collect;
+pub mod direction;
+pub mod dynamic;
+pub mod event_queue;
+pub mod float;
+pub mod generate;
+pub mod geometry;
+pub mod gis;
+pub mod grid;
+pub mod input;
+pub mod observer;
+pub mod orthogonal;
+pub mod output;
+pub mod parallel;
+pub mod polyline;
+pub mod primitive;
+pub mod svg;
+pub mod sweep_line;
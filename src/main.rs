@@ -1,59 +1,920 @@
-mod event_queue;
-mod geometry;
-mod sweep_line;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Read, Seek, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{env, fs, process, thread};
 
-use std::io::Write;
-use std::time::Instant;
-use std::{env, fs};
+use cg_3_sweep_line::algorithm::{self, Algorithm, Intersections, RunOptions};
+use cg_3_sweep_line::animation;
+use cg_3_sweep_line::binary;
+use cg_3_sweep_line::brute_force;
+use cg_3_sweep_line::collect;
+use cg_3_sweep_line::generate::{self, GenerateOptions};
+use cg_3_sweep_line::geometry::Point;
+use cg_3_sweep_line::input::{InputFormat, SegmentReader};
+use cg_3_sweep_line::output::{self, FloatFormat, OutputFormat, WriteOptions};
+use cg_3_sweep_line::polyline::{self, Overlap, Polyline};
+use cg_3_sweep_line::svg::{self, RenderOptions};
+use log::{error, info, warn};
+use serde_json::json;
 
-use geometry::Line;
-use log::info;
+const USAGE: &str = "\
+Usage: cg-3-sweep-line [command] [options] <files>...
 
-use crate::event_queue::EventQueue;
+Commands:
+  intersect         Write the intersections of every file to <file>.i (default)
+  count             Print the number of intersections of every file
+  verify            Compare the intersections of the sweep with a brute force search
+  generate <n>      Write n random segments to stdout or --output
+  render            Draw the segments and intersections to <file>.svg
+  convert <in> <out>
+                    Convert segments or intersections between the text and binary formats
 
-fn read_file(file: &str) -> Vec<Line> {
-    let contents = fs::read_to_string(file).expect("Should have been able to read the file");
+Options:
+  -o, --output <path>       Output of a single input file, - for stdout
+      --no-overwrite        Fail instead of replacing existing output files
+  -q, --quiet               Only log warnings and errors
+      --stats json          Write run statistics of every input file as one JSON line to stderr
+  -j, --jobs <n>            Process n files at once, 0 for one per CPU; a failing file does not stop the others
+      --slabs <n>           Sweep every file in n vertical slabs on parallel threads, 0 for one per CPU
+      --direction <degrees> Sweep along this direction instead of the x axis, allows vertical segments
+      --algorithm <name>    sweep (default), grid, brute, orthogonal or auto, which picks one by the segment lengths;
+                            the sweep and auto use orthogonal if all segments are horizontal or vertical
+  -h, --help                Show this help
 
-    contents
-        .lines()
-        .map(|l| l.parse().expect("Failed to parse a line"))
-        .collect()
+Input:
+      --format <format>     segments, wkt, geojson, binary or polylines, by default picked by the file extension
+      --skip-invalid        Skip invalid lines and segments with a warning instead of failing
+
+intersect:
+      --output-format <format>
+                            txt, csv, json, geojson or binary
+      --with-segments       Add the coordinates of both segments to csv, json and geojson
+      --with-parameters     Add the position t on both segments, the crossing angle and orientation to csv, json and geojson
+      --exact               Write the intersections as computed instead of rounded to 9 decimals
+      --hex                 Write hexadecimal floats
+      --round <n>           Round to n decimals
+
+generate:
+      --distribution <name> uniform (default), clustered, grid, bundles, stars or adversarial
+      --seed <seed>         Seed of the random numbers, random by default
+      --size <size>         Segments lie roughly in [0, size)², default 1000
+      --length <length>     Maximum length of the short segments, default 10
+
+count:
+      --per-segment         Also write the intersections of every segment, their histogram and the top segments
+                            to <file>.counts
+      --top <n>             Number of top segments in <file>.counts, default 10
+
+render:
+      --stroke-width <px>   Stroke width of the segments
+      --point-radius <r>    Radius of the intersection markers
+      --color-by-count      Color the segments by their number of intersections
+      --animate html|svg    Write a sweep animation to <file>.html or <file>.frames instead
+
+Exit status: 0 on success, 1 if an input cannot be processed or verify fails, 2 for invalid arguments.
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Command {
+    #[default]
+    Intersect,
+    Count,
+    Verify,
+    Generate,
+    Render,
+    Convert,
 }
 
-fn main() {
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+impl FromStr for Command {
+    type Err = String;
 
-    let params = env::args().collect::<Vec<_>>();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "intersect" => Ok(Command::Intersect),
+            "count" => Ok(Command::Count),
+            "verify" => Ok(Command::Verify),
+            "generate" => Ok(Command::Generate),
+            "render" => Ok(Command::Render),
+            "convert" => Ok(Command::Convert),
+            _ => Err(format!("Unknown command: {}", s)),
+        }
+    }
+}
 
-    for param in params.iter().skip(1) {
-        info!("Processing file {}", param);
-        let lines = read_file(param);
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StatsFormat {
+    /// One JSON object per input file on stderr
+    Json,
+}
 
-        let start_init = Instant::now();
-        let queue = EventQueue::new(lines);
-        let init = start_init.elapsed();
-        let start_sweep = Instant::now();
-        let intersections = queue.sweep();
-        let swept = start_sweep.elapsed();
-        let total = start_init.elapsed();
-
-        info!("Initializing events: {:.2?}", init);
-        info!("Sweeping line: {:.2?}", swept);
-        info!("Total elapsed: {:.2?}", total);
-        info!("intersections: {}", intersections.len());
-
-        // create a new file "i_<filename>" with the intersections
-        let filename = format!("{}.i", param);
-        // delete file if it exists
-        if fs::metadata(&filename).is_ok() {
-            fs::remove_file(&filename).expect("Failed to delete file");
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Animation {
+    /// A self-contained `<file>.html` player
+    Html,
+    /// One SVG per event in the directory `<file>.frames`
+    Svg,
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    command: Command,
+    output: Option<String>,
+    no_overwrite: bool,
+    quiet: bool,
+    help: bool,
+    stats: Option<StatsFormat>,
+    /// Process the files on a pool of this many threads, see [`process_files`]
+    jobs: Option<usize>,
+    /// Sweep every file in this many vertical slabs on parallel threads
+    slabs: Option<usize>,
+    algorithm: Algorithm,
+    /// Sweep along this direction in degrees instead of the x axis
+    direction: Option<f64>,
+    animate: Option<Animation>,
+    render: RenderOptions,
+    skip_invalid: bool,
+    format: Option<InputFormat>,
+    output_format: OutputFormat,
+    write: WriteOptions,
+    /// Write the number of intersections of every segment for the count command
+    per_segment: bool,
+    /// Number of segments with the most intersections listed with `per_segment`
+    top: Option<usize>,
+    generate: GenerateOptions,
+    seed: Option<u64>,
+    files: Vec<String>,
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("{} expects a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter().peekable();
+
+    // without a command the files are intersected, as before there were commands
+    if let Some(command) = args.peek().and_then(|arg| arg.parse().ok()) {
+        options.command = command;
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => options.output = Some(parse_value(&arg, args.next())?),
+            "--no-overwrite" => options.no_overwrite = true,
+            "-q" | "--quiet" => options.quiet = true,
+            "-h" | "--help" => options.help = true,
+            "--stats" => {
+                let value = args.next().ok_or("--stats expects json")?;
+                options.stats = match value.as_str() {
+                    "json" => Some(StatsFormat::Json),
+                    _ => return Err(format!("Unknown statistics format: {}", value)),
+                };
+            }
+            "-j" | "--jobs" => options.jobs = Some(parse_value(&arg, args.next())?),
+            "--slabs" => options.slabs = Some(parse_value(&arg, args.next())?),
+            "--algorithm" => options.algorithm = parse_value(&arg, args.next())?,
+            "--direction" => {
+                let degrees: f64 = parse_value(&arg, args.next())?;
+                if !degrees.is_finite() {
+                    return Err(format!(
+                        "--direction expects a finite angle, found {}",
+                        degrees
+                    ));
+                }
+                options.direction = Some(degrees);
+            }
+            "--animate" => {
+                let value = args.next().ok_or("--animate expects html or svg")?;
+                options.animate = match value.as_str() {
+                    "html" => Some(Animation::Html),
+                    "svg" => Some(Animation::Svg),
+                    _ => return Err(format!("Unknown animation format: {}", value)),
+                };
+            }
+            "--stroke-width" => options.render.stroke_width = parse_value(&arg, args.next())?,
+            "--point-radius" => options.render.point_radius = Some(parse_value(&arg, args.next())?),
+            "--color-by-count" => options.render.color_by_count = true,
+            "--skip-invalid" => options.skip_invalid = true,
+            "--format" => options.format = Some(parse_value(&arg, args.next())?),
+            "--output-format" => options.output_format = parse_value(&arg, args.next())?,
+            "--with-segments" => options.write.with_segments = true,
+            "--with-parameters" => options.write.with_parameters = true,
+            "--hex" => options.write.floats = FloatFormat::Hex,
+            "--round" => options.write.round = Some(parse_value(&arg, args.next())?),
+            "--exact" => options.write.exact = true,
+            "--per-segment" => options.per_segment = true,
+            "--top" => options.top = Some(parse_value(&arg, args.next())?),
+            "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+            "--size" => options.generate.size = parse_value(&arg, args.next())?,
+            "--length" => options.generate.max_length = parse_value(&arg, args.next())?,
+            "--distribution" => options.generate.distribution = parse_value(&arg, args.next())?,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("Unknown option: {}", arg))
+            }
+            _ => options.files.push(arg),
+        }
+    }
+
+    if options.help {
+        return Ok(options);
+    }
+    match options.command {
+        Command::Generate => {
+            let [count] = options.files.as_slice() else {
+                return Err("generate expects the number of segments".to_string());
+            };
+            options.generate.count = parse_value("generate", Some(count.clone()))?;
+        }
+        Command::Convert => {
+            if options.files.len() != 2 {
+                return Err("convert expects an input and an output file".to_string());
+            }
+        }
+        _ => {
+            if options.files.is_empty() {
+                return Err("No input files".to_string());
+            }
+            if options.output.is_some() && options.files.len() > 1 {
+                return Err("--output expects a single input file".to_string());
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+/// Points closer than this are the same intersection, both results are rounded to 9 decimals.
+const VERIFY_TOLERANCE: f64 = 1e-8;
+
+/// Default number of segments with the most intersections listed by `count --per-segment`.
+const TOP_SEGMENTS: usize = 10;
+
+/// Fails if the output exists and must not be overwritten.
+fn check_overwrite(path: &str, options: &Options) -> Result<(), String> {
+    if options.no_overwrite && Path::new(path).exists() {
+        return Err(format!("{} already exists, not overwriting it", path));
+    }
+    Ok(())
+}
+
+/// Creates a buffered output file, `-` writes to stdout.
+fn create_output(path: &str, options: &Options) -> Result<Box<dyn Write>, String> {
+    if path == "-" {
+        return Ok(Box::new(io::stdout().lock()));
+    }
+    check_overwrite(path, options)?;
+    let file =
+        fs::File::create(path).map_err(|err| format!("Failed to create {}: {}", path, err))?;
+    Ok(Box::new(io::BufWriter::new(file)))
+}
+
+/// Streams text segments, or points with `is_points`, into the binary format.
+fn write_binary<R: BufRead, W: Write + Seek>(
+    reader: SegmentReader<R>,
+    is_points: bool,
+    writer: W,
+    output: &str,
+) -> Result<W, String> {
+    let write_error = |err: io::Error| format!("Failed to write {}: {}", output, err);
+    let mut writer = match is_points {
+        true => binary::StreamWriter::intersections(writer),
+        false => binary::StreamWriter::segments(writer),
+    }
+    .map_err(write_error)?;
+    // the reader hands over one record at a time, the first write error stops writing
+    let mut written = Ok(());
+    let read = if is_points {
+        reader.read_points(|point| {
+            if written.is_ok() {
+                written = writer.write_point(&point);
+            }
+        })
+    } else {
+        reader.read(|line| {
+            if written.is_ok() {
+                written = writer.write_segment(&line);
+            }
+        })
+    };
+    read.map_err(|err| err.to_string())?;
+    written.map_err(write_error)?;
+    writer.finish().map_err(write_error)
+}
+
+/// Converts between the text and binary formats, the direction is picked by the magic number of the input.
+///
+/// Text input with two values per line is read as intersections, otherwise as segments. Both are streamed, the
+/// header of a binary file is filled in after the records.
+fn convert(input: &str, output: &str, options: &Options) -> Result<(), String> {
+    let write_error = |err: io::Error| format!("Failed to write {}: {}", output, err);
+    let read_error = |err: io::Error| format!("Failed to read {}: {}", input, err);
+    let mut reader = open(input)?.into_inner();
+
+    let start = reader.fill_buf().map_err(read_error)?;
+    let (is_binary, is_segments) = (
+        binary::is_binary(start),
+        start.starts_with(&binary::SEGMENTS_MAGIC),
+    );
+    if is_binary {
+        let mut writer = create_output(output, options)?;
+        if is_segments {
+            let mut written = Ok(());
+            binary::read_segments(reader, |line| {
+                if written.is_ok() {
+                    written = writeln!(writer, "{}", line);
+                }
+            })
+            .map_err(read_error)?;
+            written.map_err(write_error)?;
+        } else {
+            for point in binary::read_intersections(reader).map_err(read_error)? {
+                writeln!(writer, "{}", point).map_err(write_error)?;
+            }
+        }
+        writer.flush().map_err(write_error)?;
+        info!("Converted {} to text file {}", input, output);
+        return Ok(());
+    }
+
+    // the first line with content tells points from segments, the lines up to it are read again
+    let mut head = Vec::new();
+    let is_points = loop {
+        let start = head.len();
+        if reader.read_until(b'\n', &mut head).map_err(read_error)? == 0 {
+            break false;
+        }
+        let line = std::str::from_utf8(&head[start..])
+            .map_err(|_| format!("{} is not a text file", input))?;
+        let content = line.split('#').next().unwrap_or_default();
+        if !content.trim().is_empty() {
+            break content.split_whitespace().count() == 2;
+        }
+    };
+    let reader = SegmentReader::new(input, head.as_slice().chain(reader));
+
+    if output == "-" {
+        // stdout cannot seek back to the header, so the binary output is kept in memory
+        let data =
+            write_binary(reader, is_points, io::Cursor::new(Vec::new()), output)?.into_inner();
+        io::stdout().lock().write_all(&data).map_err(write_error)?;
+    } else {
+        check_overwrite(output, options)?;
+        let file = fs::File::create(output)
+            .map_err(|err| format!("Failed to create {}: {}", output, err))?;
+        write_binary(reader, is_points, file, output)?;
+    }
+    info!("Converted {} to binary file {}", input, output);
+    Ok(())
+}
+
+/// Writes random segments as text, or in the binary format if the output ends with `.bin`.
+fn generate(options: &Options) -> Result<(), String> {
+    let seed = options.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    let generate_options = GenerateOptions {
+        seed,
+        ..options.generate.clone()
+    };
+    info!(
+        "Generating {} {:?} segments with seed {}",
+        generate_options.count, generate_options.distribution, seed
+    );
+    let lines = generate::generate(&generate_options)?;
+
+    let path = options.output.as_deref().unwrap_or("-");
+    let mut writer = create_output(path, options)?;
+    let result = if InputFormat::from_path(path) == InputFormat::Binary {
+        binary::write_segments(&mut writer, &lines)
+    } else {
+        lines
+            .iter()
+            .try_for_each(|line| writeln!(writer, "{}", line))
+    };
+    result
+        .and_then(|_| writer.flush())
+        .map_err(|err| format!("Failed to write {}: {}", path, err))
+}
+
+thread_local! {
+    /// Log output of the file processed by this thread, `None` writes directly to stderr.
+    static LOG_BUFFER: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Target of the logger, which keeps the logs of files processed in parallel apart.
+struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        LOG_BUFFER.with(|buffer| match buffer.borrow_mut().as_mut() {
+            Some(buffer) => buffer.write(buf),
+            None => io::stderr().write(buf),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// Counts of one successfully processed file, for the summary of [`process_files`].
+struct FileSummary {
+    segments: usize,
+    intersections: usize,
+}
+
+/// Processes the files on `jobs` threads and prints a summary table, failing files do not stop the others.
+///
+/// The logs of every file are buffered and printed together once the file is done.
+fn process_files(options: &Options, jobs: usize) -> Result<(), String> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, options.files.len()) {
+            scope.spawn(|| {
+                while let Some(param) = options.files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    LOG_BUFFER.with(|buffer| *buffer.borrow_mut() = Some(Vec::new()));
+                    let start = Instant::now();
+                    // the panic itself is logged by the hook installed in main
+                    let result = panic::catch_unwind(|| process_file(param, options))
+                        .unwrap_or_else(|_| Err(format!("{}: processing failed", param)));
+                    let elapsed = start.elapsed();
+                    if let Err(message) = &result {
+                        error!("{}", message);
+                    }
+                    let log = LOG_BUFFER.with(|buffer| buffer.borrow_mut().take());
+                    // a failing stderr only loses the log, the summary still reports the file
+                    let _ = io::stderr().lock().write_all(&log.unwrap_or_default());
+                    results.lock().unwrap().push((param, result, elapsed));
+                }
+            });
         }
-        let mut file = fs::File::create(&filename).expect("Failed to create file");
-        intersections
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(param, ..)| options.files.iter().position(|file| file == *param));
+    let failed = results
+        .iter()
+        .filter(|(_, result, _)| result.is_err())
+        .count();
+    if !options.quiet {
+        let width = options
+            .files
             .iter()
-            .map(|p| format!("{}", p))
-            .for_each(|p| writeln!(file, "{}", p).expect("Failed to write to file"));
-        info!("Wrote intersections to file {}", filename);
+            .map(String::len)
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut table = format!(
+            "{:<width$}  {:<6}  {:>10}  {:>13}  {:>10}\n",
+            "file", "status", "segments", "intersections", "time"
+        );
+        for (param, result, elapsed) in &results {
+            let (status, segments, intersections) = match result {
+                Ok(summary) => (
+                    "ok",
+                    summary.segments.to_string(),
+                    summary.intersections.to_string(),
+                ),
+                Err(_) => ("failed", "-".to_string(), "-".to_string()),
+            };
+            table += &format!(
+                "{:<width$}  {:<6}  {:>10}  {:>13}  {:>10}\n",
+                param,
+                status,
+                segments,
+                intersections,
+                format!("{:.2?}", elapsed)
+            );
+        }
+        eprint!("{}", table);
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} files failed", failed, results.len()));
+    }
+    Ok(())
+}
+
+fn open(param: &str) -> Result<SegmentReader<Box<dyn io::BufRead>>, String> {
+    SegmentReader::open(param).map_err(|err| format!("Failed to open {}: {}", param, err))
+}
+
+/// Reads the polylines of one input file and writes their crossings, see [`polyline::crossings`].
+///
+/// Only the text output names the polylines and segments of every crossing, the count and verify commands work
+/// on the distinct crossing points.
+fn process_polylines(param: &str, options: &Options) -> Result<FileSummary, String> {
+    if options.command == Command::Render {
+        return Err(format!("{}: polylines cannot be rendered", param));
+    }
+    let ignored: Vec<_> = [
+        ("--algorithm", options.algorithm != Algorithm::Sweep),
+        ("--slabs", options.slabs.is_some()),
+        ("--direction", options.direction.is_some()),
+        ("--stats", options.stats.is_some()),
+        ("--per-segment", options.per_segment),
+    ]
+    .into_iter()
+    .filter_map(|(flag, given)| given.then_some(flag))
+    .collect();
+    if !ignored.is_empty() {
+        warn!(
+            "Ignoring {}, polylines are swept by their own sweep",
+            ignored.join(", ")
+        );
+    }
+
+    let start = Instant::now();
+    let mut polylines = Vec::new();
+    let summary = open(param)?
+        .skip_invalid(options.skip_invalid)
+        .read_polylines(|polyline| polylines.push(polyline))
+        .map_err(|err| err.to_string())?;
+    if summary.skipped > 0 {
+        warn!("Skipped {} invalid lines", summary.skipped);
+    }
+    // consecutive points may be shared, but not repeated; skipped polylines keep the ids of the others
+    let mut ids = Vec::new();
+    let mut skipped = 0;
+    let mut valid = Vec::new();
+    for (id, polyline) in polylines.into_iter().enumerate() {
+        if let Some(line) = polyline.segments().find(|line| line.p == line.q) {
+            let error = format!("polyline {} repeats the point {}", id, line.p);
+            if !options.skip_invalid {
+                return Err(format!("{}: {}", param, error));
+            }
+            warn!("Skipping {}", error);
+            skipped += 1;
+            continue;
+        }
+        ids.push(id);
+        valid.push(polyline);
+    }
+    if skipped > 0 {
+        warn!("Skipped {} invalid polylines", skipped);
+    }
+    let count = valid.iter().map(Polyline::segment_count).sum();
+    info!(
+        "Reading {} polylines with {} segments: {:.2?}",
+        valid.len(),
+        count,
+        start.elapsed()
+    );
+
+    let overlap_error = |overlap: Overlap| {
+        let overlap = Overlap {
+            a: (ids[overlap.a.0], overlap.a.1),
+            b: (ids[overlap.b.0], overlap.b.1),
+        };
+        format!("{}: {}, which has no single crossing point", param, overlap)
+    };
+    let start_sweep = Instant::now();
+    // the panic itself is logged by the hook installed in main
+    let mut crossings = panic::catch_unwind(|| polyline::crossings(&valid))
+        .map_err(|_| format!("{}: the sweep failed", param))?
+        .map_err(overlap_error)?;
+    for crossing in crossings
+        .between
+        .iter_mut()
+        .chain(&mut crossings.self_crossings)
+    {
+        crossing.a.polyline = ids[crossing.a.polyline];
+        crossing.b.polyline = ids[crossing.b.polyline];
+    }
+    let intersections = crossings.points();
+    info!("Sweeping line: {:.2?}", start_sweep.elapsed());
+    info!(
+        "crossings: {} between polylines, {} self-crossings at {} points",
+        crossings.between.len(),
+        crossings.self_crossings.len(),
+        intersections.len()
+    );
+
+    match options.command {
+        Command::Intersect => {
+            let path = intersect_path(param, options);
+            let mut writer = create_output(&path, options)?;
+            output::write_crossings(
+                &mut writer,
+                options.output_format,
+                &crossings,
+                &options.write,
+            )
+            .and_then(|_| writer.flush())
+            .map_err(|err| format!("Failed to write {}: {}", path, err))?;
+            if path != "-" {
+                info!("Wrote crossings to file {}", path);
+            }
+        }
+        Command::Count => write_count(param, options, intersections.len())?,
+        Command::Verify => {
+            let expected = polyline::brute_force(&valid)
+                .map_err(overlap_error)?
+                .points();
+            check_verified(
+                param,
+                &intersections,
+                &expected,
+                ("crossing", "crossing points"),
+            )?;
+        }
+        Command::Render | Command::Generate | Command::Convert => {
+            unreachable!("not a polyline command")
+        }
+    }
+    Ok(FileSummary {
+        segments: count,
+        intersections: intersections.len(),
+    })
+}
+
+/// The output of the intersect command for one input, stdin writes to stdout.
+fn intersect_path(param: &str, options: &Options) -> String {
+    match &options.output {
+        Some(path) => path.clone(),
+        None if param == "-" => "-".to_string(),
+        None => format!("{}.{}", param, options.output_format.extension()),
+    }
+}
+
+/// Prints the number of intersections like wc, naming the file unless reading from stdin.
+fn write_count(param: &str, options: &Options, found: usize) -> Result<(), String> {
+    let path = options.output.as_deref().unwrap_or("-");
+    let mut writer = create_output(path, options)?;
+    let result = if param == "-" {
+        writeln!(writer, "{}", found)
+    } else {
+        writeln!(writer, "{} {}", found, param)
+    };
+    result
+        .and_then(|_| writer.flush())
+        .map_err(|err| format!("Failed to write {}: {}", path, err))
+}
+
+/// Fails with the differences if the sweep did not find the same points as brute force, `kind` names a single
+/// point and several of them.
+fn check_verified(
+    param: &str,
+    found: &BTreeSet<Point>,
+    expected: &BTreeSet<Point>,
+    (kind, kinds): (&str, &str),
+) -> Result<(), String> {
+    let missed = brute_force::missing(expected, found, VERIFY_TOLERANCE);
+    let extra = brute_force::missing(found, expected, VERIFY_TOLERANCE);
+    if !missed.is_empty() || !extra.is_empty() {
+        for point in missed.iter().take(10) {
+            warn!("Missed {} {}", kind, point);
+        }
+        for point in extra.iter().take(10) {
+            warn!("Extra {} {}", kind, point);
+        }
+        return Err(format!(
+            "{}: the sweep found {} {}, brute force {} ({} missed, {} extra)",
+            param,
+            found.len(),
+            kinds,
+            expected.len(),
+            missed.len(),
+            extra.len()
+        ));
+    }
+    info!("Verified {} {} against brute force", found.len(), kinds);
+    Ok(())
+}
+
+/// Writes the result of the command for one input file.
+fn write_output(param: &str, options: &Options, found: &Intersections) -> Result<(), String> {
+    // stdin as input writes the intersections to stdout, other outputs are named after "stdin"
+    let name = if param == "-" { "stdin" } else { param };
+    let write_error = |path: &str, err: io::Error| format!("Failed to write {}: {}", path, err);
+    let output = |extension: &str| {
+        options
+            .output
+            .clone()
+            .unwrap_or_else(|| format!("{}.{}", name, extension))
+    };
+    match options.command {
+        Command::Intersect => {
+            let path = intersect_path(param, options);
+            let mut writer = create_output(&path, options)?;
+            output::write_result(&mut writer, options.output_format, found, &options.write)
+                .and_then(|_| writer.flush())
+                .map_err(|err| write_error(&path, err))?;
+            if path != "-" {
+                info!("Wrote intersections to file {}", path);
+            }
+        }
+        Command::Count => {
+            write_count(param, options, found.found)?;
+            if let Some(counts) = found.counts.as_ref().filter(|_| options.per_segment) {
+                let path = format!("{}.counts", name);
+                let mut writer = create_output(&path, options)?;
+                let top = options.top.unwrap_or(TOP_SEGMENTS);
+                output::write_segment_counts(&mut writer, counts, top)
+                    .and_then(|_| writer.flush())
+                    .map_err(|err| write_error(&path, err))?;
+                info!("Wrote the intersections of every segment to {}", path);
+            }
+        }
+        Command::Verify => {
+            let expected = found
+                .brute_force()
+                .map_err(|err| format!("{}: {}", param, err))?;
+            check_verified(
+                param,
+                &found.points,
+                &expected,
+                ("intersection", "intersections"),
+            )?;
+        }
+        Command::Render => match (options.animate, &found.recorder) {
+            (Some(Animation::Html), Some(recorder)) => {
+                let path = output("html");
+                check_overwrite(&path, options)?;
+                recorder
+                    .write_html(Path::new(&path))
+                    .map_err(|err| write_error(&path, err))?;
+                info!("Wrote sweep animation to file {}", path);
+            }
+            (Some(Animation::Svg), Some(recorder)) => {
+                let dir = output("frames");
+                check_overwrite(&dir, options)?;
+                recorder
+                    .write_frames(Path::new(&dir))
+                    .map_err(|err| write_error(&dir, err))?;
+                info!("Wrote {} frames to {}", recorder.frames().len(), dir);
+            }
+            _ => {
+                let path = output("svg");
+                let counts = found
+                    .counts
+                    .as_ref()
+                    .map(|counts| counts.counts.as_slice())
+                    .unwrap_or_default();
+                let svg = svg::render(
+                    &found.segments.lines,
+                    &found.points,
+                    counts,
+                    &options.render,
+                );
+                let mut writer = create_output(&path, options)?;
+                writer
+                    .write_all(svg.as_bytes())
+                    .and_then(|_| writer.flush())
+                    .map_err(|err| write_error(&path, err))?;
+                info!("Rendered segments and intersections to {}", path);
+            }
+        },
+        Command::Generate | Command::Convert => unreachable!("not an input file command"),
+    }
+    Ok(())
+}
+
+/// Reads and sweeps one input file, then writes the result of the command.
+fn process_file(param: &str, options: &Options) -> Result<FileSummary, String> {
+    info!("Processing file {}", param);
+    let format = options
+        .format
+        .unwrap_or_else(|| InputFormat::from_path(param));
+    if format == InputFormat::Polylines {
+        return process_polylines(param, options);
+    }
+    let structured_output = !matches!(
+        options.output_format,
+        OutputFormat::Txt | OutputFormat::Binary
+    );
+    let feature_input = matches!(format, InputFormat::Wkt | InputFormat::GeoJson);
+    let per_segment = options.command == Command::Count && options.per_segment;
+    // the frames grow with the number of events times the segments drawn in each of them
+    let record = match options
+        .animate
+        .filter(|_| options.command == Command::Render)
+    {
+        Some(Animation::Html) => Some(animation::MAX_HTML_SEGMENTS),
+        Some(Animation::Svg) => Some(animation::MAX_FRAME_SEGMENTS),
+        None => None,
+    };
+    let run_options = RunOptions {
+        algorithm: options.algorithm,
+        slabs: options.slabs,
+        direction: options.direction,
+        keep_lines: matches!(options.command, Command::Verify | Command::Render),
+        // structured output and feature input name the segments of every intersection
+        pairs: options.command == Command::Intersect
+            && (structured_output || options.write.exact || feature_input),
+        counts: options.command == Command::Render || per_segment,
+        record,
+        stats: options.stats.is_some(),
+        count_only: options.command == Command::Count,
+    }
+    .resolve(format)
+    .map_err(|err| format!("{}: {}", param, err))?;
+
+    let start = Instant::now();
+    let collect_options = run_options.collect_options(options.skip_invalid);
+    let input =
+        collect::read(open(param)?, format, collect_options).map_err(|err| err.to_string())?;
+    let read = start.elapsed();
+    let init = input.init;
+    let start_sweep = Instant::now();
+    // the panic itself is logged by the hook installed in main
+    let found = panic::catch_unwind(AssertUnwindSafe(|| algorithm::run(input, &run_options)))
+        .map_err(|_| format!("{}: the sweep failed", param))?
+        .map_err(|err| format!("{}: {}", param, err))?;
+    let swept = start_sweep.elapsed();
+    info!(
+        "Reading {} segments and initializing events: {:.2?}",
+        found.segment_count, read
+    );
+    info!("Sweeping line: {:.2?}", swept);
+    info!("Total elapsed: {:.2?}", start.elapsed());
+    info!("intersections: {}", found.found);
+
+    let start_write = Instant::now();
+    write_output(param, options, &found)?;
+    let written = start_write.elapsed();
+
+    if let (Some(StatsFormat::Json), Some(stats)) = (options.stats, found.stats) {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let record = json!({
+            "file": param,
+            "segments": found.segment_count,
+            "intersections": found.found,
+            "events": stats.events,
+            "max_status_size": stats.max_status_size,
+            "duplicates_discarded": stats.duplicates_discarded,
+            "timings_ms": {
+                "parse": ms(read.saturating_sub(init)),
+                "init": ms(init),
+                "sweep": ms(swept),
+                "write": ms(written),
+                "total": ms(start.elapsed()),
+            },
+        });
+        writeln!(LogWriter, "{}", record)
+            .map_err(|err| format!("Failed to write statistics: {}", err))?;
+    }
+    Ok(FileSummary {
+        segments: found.segment_count,
+        intersections: found.found,
+    })
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\nRun with --help for usage", message);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let level = if options.quiet { "warn" } else { "info" };
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level));
+    if options.jobs.is_some() {
+        logger.target(env_logger::Target::Pipe(Box::new(LogWriter)));
+    }
+    logger.init();
+    panic::set_hook(Box::new(|info| error!("{}", info)));
+
+    let result = match options.command {
+        Command::Generate => generate(&options),
+        Command::Convert => convert(&options.files[0], &options.files[1], &options),
+        _ => match options.jobs {
+            Some(0) => process_files(
+                &options,
+                thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            ),
+            Some(jobs) => process_files(&options, jobs),
+            None => options
+                .files
+                .iter()
+                .try_for_each(|param| process_file(param, &options).map(|_| ())),
+        },
+    };
+    if let Err(message) = result {
+        error!("{}", message);
+        process::exit(1);
     }
 }
 
@@ -61,7 +922,12 @@ fn main() {
 mod tests {
     use std::str::FromStr;
 
-    use crate::geometry::Point;
+    use std::path::PathBuf;
+
+    use cg_3_sweep_line::event_queue::EventQueue;
+    use cg_3_sweep_line::generate::Distribution;
+    use cg_3_sweep_line::geometry::Line;
+    use cg_3_sweep_line::input::read_file;
 
     use super::*;
 
@@ -265,10 +1131,383 @@ mod tests {
         assert_eq!(intersections[2].y, 0.5);
     }
 
+    #[test]
+    fn test_parse_args() {
+        let args = ["--animate", "html", "a.dat", "b.dat"].map(String::from);
+        let options = parse_args(args).unwrap();
+        assert_eq!(options.animate, Some(Animation::Html));
+        assert_eq!(options.files, vec!["a.dat", "b.dat"]);
+
+        assert!(parse_args(["--animate".to_string()]).is_err());
+        assert!(parse_args(["--animate", "gif"].map(String::from)).is_err());
+
+        let args = [
+            "render",
+            "-o",
+            "out.svg",
+            "--stroke-width",
+            "2",
+            "--color-by-count",
+            "a.dat",
+        ];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert_eq!(options.command, Command::Render);
+        assert_eq!(options.output.as_deref(), Some("out.svg"));
+        assert_eq!(options.render.stroke_width, 2.0);
+        assert!(options.render.color_by_count);
+
+        assert!(parse_args(["--point-radius", "big"].map(String::from)).is_err());
+
+        let args = [
+            "--output-format",
+            "geojson",
+            "--with-segments",
+            "--with-parameters",
+            "a.dat",
+        ];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert_eq!(options.output_format, OutputFormat::GeoJson);
+        assert!(options.write.with_segments);
+        assert!(options.write.with_parameters);
+        assert!(parse_args(["--output-format", "xml"].map(String::from)).is_err());
+        assert!(parse_args(["-o", "out.i", "a.dat", "b.dat"].map(String::from)).is_err());
+
+        let args = ["--exact", "--hex", "--round", "3", "a.dat"];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert!(options.write.exact);
+        assert_eq!(options.write.floats, FloatFormat::Hex);
+        assert_eq!(options.write.round, Some(3));
+        assert!(parse_args(["--round", "-1", "a.dat"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let options = parse_args(["a.dat".to_string()]).unwrap();
+        assert_eq!(options.command, Command::Intersect);
+
+        let args = ["count", "--quiet", "--no-overwrite", "-"];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert_eq!(options.command, Command::Count);
+        assert!(options.quiet && options.no_overwrite);
+        assert_eq!(options.files, vec!["-"]);
+
+        let args = ["count", "--per-segment", "--top", "3", "a.dat"];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert!(options.per_segment);
+        assert_eq!(options.top, Some(3));
+        assert!(parse_args(["count", "--top", "x", "a.dat"].map(String::from)).is_err());
+
+        let args = ["generate", "500", "--seed", "7", "--size", "100"];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert_eq!(options.command, Command::Generate);
+        assert_eq!(options.generate.count, 500);
+        assert_eq!(options.generate.size, 100.0);
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.generate.distribution, Distribution::Uniform);
+
+        let args = ["generate", "10", "--distribution", "gaussian"];
+        let options = parse_args(args.map(String::from)).unwrap();
+        assert_eq!(options.generate.distribution, Distribution::Clustered);
+
+        assert!(parse_args(["generate".to_string()]).is_err());
+        assert!(parse_args(["generate", "many"].map(String::from)).is_err());
+        assert!(parse_args(["convert", "a.dat"].map(String::from)).is_err());
+        assert!(parse_args(["verify".to_string()]).is_err());
+        assert!(parse_args(["--frobnicate", "a.dat"].map(String::from)).is_err());
+        assert!(parse_args(["-h".to_string()]).unwrap().help);
+
+        let options = parse_args(["--stats", "json", "a.dat"].map(String::from)).unwrap();
+        assert_eq!(options.stats, Some(StatsFormat::Json));
+        assert!(parse_args(["--stats", "xml", "a.dat"].map(String::from)).is_err());
+
+        let options = parse_args(["-j", "4", "a.dat", "b.dat"].map(String::from)).unwrap();
+        assert_eq!(options.jobs, Some(4));
+        assert!(parse_args(["--jobs", "-1", "a.dat"].map(String::from)).is_err());
+        let options = parse_args(["--slabs", "0", "a.dat"].map(String::from)).unwrap();
+        assert_eq!(options.slabs, Some(0));
+        let options = parse_args(["--algorithm", "auto", "a.dat"].map(String::from)).unwrap();
+        assert_eq!(options.algorithm, Algorithm::Auto);
+        assert!(parse_args(["--algorithm", "rtree", "a.dat"].map(String::from)).is_err());
+        let options = parse_args(["--direction", "-30", "a.dat"].map(String::from)).unwrap();
+        assert_eq!(options.direction, Some(-30.0));
+        for degrees in ["NaN", "inf", "-infinity"] {
+            assert!(parse_args(["--direction", degrees, "a.dat"].map(String::from)).is_err());
+        }
+    }
+
+    /// A directory for the files of one test, removed with everything in it when the test ends.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("sweep-{}-{}", name, process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        /// The path of a file in the directory, as passed on the command line.
+        fn path(&self, name: &str) -> String {
+            self.0.join(name).to_str().unwrap().to_string()
+        }
+
+        /// Writes the file and returns its path.
+        fn write(&self, name: &str, contents: &str) -> String {
+            let path = self.path(name);
+            fs::write(&path, contents).unwrap();
+            path
+        }
+
+        fn read(&self, name: &str) -> String {
+            fs::read_to_string(self.path(name)).unwrap()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            // a failing test may not have created every file, leftovers in the temp dir are harmless
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let dir = TempDir::new("convert");
+        let options = Options::default();
+
+        let input = dir.write("a.dat", "# segments\n0 1 5 1\n1 0.1 4 -2\n");
+        convert(&input, &dir.path("a.bin"), &options).unwrap();
+        convert(&dir.path("a.bin"), &dir.path("b.dat"), &options).unwrap();
+        assert_eq!(dir.read("b.dat"), "0 1 5 1\n1 0.1 4 -2\n");
+
+        // the lines up to the first point are read again after telling points from segments
+        let input = dir.write("a.i", "# points\n\n2.5 1\n0.30000000000000004 3\n");
+        convert(&input, &dir.path("a.i.bin"), &options).unwrap();
+        convert(&dir.path("a.i.bin"), &dir.path("b.i"), &options).unwrap();
+        assert_eq!(dir.read("b.i"), "2.5 1\n0.30000000000000004 3\n");
+
+        let no_overwrite = Options {
+            no_overwrite: true,
+            ..Default::default()
+        };
+        assert!(convert(&dir.path("a.dat"), &dir.path("a.bin"), &no_overwrite).is_err());
+
+        let input = dir.write("c.i", "2.5 1\n3 x\n");
+        let error = convert(&input, &dir.path("c.i.bin"), &options).unwrap_err();
+        assert!(error.contains("c.i:2:3"));
+    }
+
+    #[test]
+    fn test_process_files() {
+        let dir = TempDir::new("jobs");
+        let options = Options {
+            quiet: true,
+            files: vec![
+                dir.write("a.dat", "0 0 1 1\n0 1 1 0\n"),
+                dir.write("b.dat", "1 0 1 2\n0 0 2 1\n"),
+                dir.write("c.dat", "0 0 2 1\n0 1 2 0\n"),
+            ],
+            ..Default::default()
+        };
+
+        // the vertical segment of b.dat fails, the other files are still processed
+        assert_eq!(
+            process_files(&options, 2),
+            Err("1 of 3 files failed".to_string())
+        );
+        assert_eq!(dir.read("a.dat.i"), "0.5 0.5\n");
+        assert_eq!(dir.read("c.dat.i"), "1 0.5\n");
+        assert!(!Path::new(&dir.path("b.dat.i")).exists());
+    }
+
+    #[test]
+    fn test_algorithm_outputs() {
+        let dir = TempDir::new("algorithms");
+        let options = Options {
+            quiet: true,
+            ..Default::default()
+        };
+
+        // axis-parallel input is swept by the orthogonal sweep, which also names the segments for the counts
+        let input = dir.write("a.dat", "0 1 4 1\n2 0 2 3\n2 3 5 3\n");
+        process_file(&input, &options).unwrap();
+        assert_eq!(dir.read("a.dat.i"), "2 1\n2 3\n");
+        let per_segment = Options {
+            command: Command::Count,
+            per_segment: true,
+            output: Some(dir.path("a.count")),
+            quiet: true,
+            ..Default::default()
+        };
+        process_file(&input, &per_segment).unwrap();
+        assert_eq!(dir.read("a.count"), format!("2 {}\n", input));
+        assert!(dir
+            .read("a.dat.counts")
+            .ends_with("# segments: segment intersections\n0 1\n1 2\n2 1\n"));
+        let stats = Options {
+            stats: Some(StatsFormat::Json),
+            quiet: true,
+            ..Default::default()
+        };
+        let error = process_file(&input, &stats).err().unwrap();
+        assert!(error.starts_with(&format!("{}: vertical segment", input)));
+
+        let input = dir.write("b.dat", "0 0 4 4\n0 4 4 0\n0 2 4 2.5\n");
+        let csv = Options {
+            algorithm: Algorithm::Grid,
+            output_format: OutputFormat::Csv,
+            quiet: true,
+            ..Default::default()
+        };
+        process_file(&input, &csv).unwrap();
+        assert!(dir
+            .read("b.dat.i.csv")
+            .starts_with("x,y,segment_a,segment_b\n1.777777778,2.222222222,1,2\n"));
+        let stats = Options {
+            algorithm: Algorithm::Brute,
+            ..stats
+        };
+        let error = process_file(&input, &stats).err().unwrap();
+        assert_eq!(
+            error,
+            format!(
+                "{}: the brute algorithm does not support the statistics",
+                input
+            )
+        );
+    }
+
+    #[test]
+    fn test_direction_outputs() {
+        let dir = TempDir::new("direction");
+        let input = dir.write("a.dat", "0 1 4 1\n2 0 2 3\n1.5 2.5 5 3\n");
+        let options = Options {
+            quiet: true,
+            direction: Some(1.0),
+            output_format: OutputFormat::Csv,
+            ..Default::default()
+        };
+
+        process_file(&input, &options).unwrap();
+        assert_eq!(
+            dir.read("a.dat.i.csv"),
+            "x,y,segment_a,segment_b\n2,1,1,0\n2,2.571428571,1,2\n"
+        );
+        let animate = Options {
+            command: Command::Render,
+            animate: Some(Animation::Html),
+            ..options
+        };
+        assert!(process_file(&input, &animate).is_err());
+    }
+
+    #[test]
+    fn test_arc_input() {
+        let dir = TempDir::new("arcs");
+        let options = Options {
+            quiet: true,
+            ..Default::default()
+        };
+
+        // the upper half of the unit circle, crossed twice by the segment
+        let input = dir.write("a.dat", "-2 0.5 2 0.5\narc 0 0 1 0 180\n");
+        let summary = process_file(&input, &options).unwrap();
+        assert_eq!(summary.intersections, 2);
+        assert_eq!(dir.read("a.dat.i"), "-0.866025404 0.5\n0.866025404 0.5\n");
+
+        let json = Options {
+            output_format: OutputFormat::Json,
+            ..options
+        };
+        assert!(process_file(&input, &json).is_err());
+    }
+
+    #[test]
+    fn test_polyline_input() {
+        let dir = TempDir::new("polylines");
+        let options = Options {
+            quiet: true,
+            ..Default::default()
+        };
+
+        // a bow tie crossing itself, a repeated point and a vertical line crossing the bow tie
+        let input = dir.write(
+            "a.polylines",
+            "0 0 2 2 2 0 0 2\n3 3 4 4 4 4\n1.5 -1 1.5 1\n",
+        );
+        assert!(process_file(&input, &options).is_err());
+        let skip = Options {
+            skip_invalid: true,
+            ..options
+        };
+        let summary = process_file(&input, &skip).unwrap();
+        assert_eq!(summary.segments, 4);
+        assert_eq!(summary.intersections, 2);
+        // the polylines keep their ids after the skipped one
+        assert_eq!(
+            dir.read("a.polylines.i"),
+            "# crossings between polylines: x y polyline segment t other_polyline other_segment other_t\n\
+             1.5 0.5 0 2 0.25 2 0 0.75\n\
+             # self-crossings: x y polyline segment t other_segment other_t\n\
+             1 1 0 0 0.5 2 0.5\n"
+        );
+
+        let verify = Options {
+            command: Command::Verify,
+            ..skip
+        };
+        assert!(process_file(&input, &verify).is_ok());
+
+        // a shared edge has no single crossing point
+        let input = dir.write("b.polylines", "0 0 1 1 2 0\n2 0 1 1 1 3\n");
+        let error = process_file(&input, &verify).err().unwrap();
+        assert!(error.contains("segment 1 of polyline 0 overlaps segment 0 of polyline 1"));
+    }
+
+    #[test]
+    fn test_feature_input() {
+        let dir = TempDir::new("features");
+        let options = Options {
+            quiet: true,
+            ..Default::default()
+        };
+
+        // the text output names the features of both segments
+        let input = dir.write(
+            "a.wkt",
+            "a LINESTRING (0 0, 1 1, 2 0)\nb LINESTRING (0 0.5, 2 0.5)\nPOLYGON ((0 2, 2 2, 1 0.2, 0 2))\n",
+        );
+        let summary = process_file(&input, &options).unwrap();
+        assert_eq!((summary.segments, summary.intersections), (6, 6));
+        assert!(dir
+            .read("a.wkt.i")
+            .starts_with("0.5 0.5 \"a\" \"b\"\n0.714285714 0.714285714 \"a\" \"3\"\n"));
+        let verify = Options {
+            command: Command::Verify,
+            quiet: true,
+            ..Default::default()
+        };
+        assert!(process_file(&input, &verify).is_ok());
+
+        let geojson = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": "a", "geometry": {"type": "LineString", "coordinates": [[0, 0], [1, 1], [2, 0]]}},
+            {"type": "Feature", "id": "b", "geometry": {"type": "LineString", "coordinates": [[1, 0], [1, 2]]}}
+        ]}"#;
+        let input = dir.write("b.geojson", geojson);
+        process_file(&input, &options).unwrap();
+        assert_eq!(dir.read("b.geojson.i"), "1 1 \"a\" \"b\"\n");
+
+        let input = dir.write(
+            "c.wkt",
+            "a LINESTRING (0 0, 2 0)\nb LINESTRING (1 0, 3 0)\n",
+        );
+        let error = process_file(&input, &options).err().unwrap();
+        assert!(error.starts_with(&format!("{}: segment 0 of feature a overlaps", input)));
+    }
+
     #[test]
     fn test_same_result_as_simple() {
         let file = "data/s_1000_10.dat";
-        let lines = read_file(file);
+        let lines = read_file(file).unwrap();
 
         let queue = EventQueue::new(lines);
         let intersections = queue.sweep();
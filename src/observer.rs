@@ -0,0 +1,478 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use crate::{
+    arena::{Arena, SegmentId},
+    event_queue::Event,
+    geometry::Point,
+    sweep_line::SweepLine,
+};
+
+/// Why an intersection found between two neighbors was not added to the event queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscardReason {
+    /// The intersection point was already scheduled by another pair of segments.
+    Duplicate,
+    /// The intersection point lies left of (or on) the sweep line and was therefore already handled.
+    BehindSweepLine,
+}
+
+/// Hooks into every step of [`EventQueue::sweep_with_observer`](crate::event_queue::EventQueue::sweep_with_observer).
+///
+/// All methods default to doing nothing, so an implementation only needs to override the steps it is interested in.
+pub trait SweepObserver {
+    /// Called right after an event was popped from the queue, before the sweep line is updated.
+    fn event_popped(&mut self, _event: &Event) {}
+
+    /// Called after a segment was inserted into the sweep line.
+    ///
+    /// Every hook names the segments by their ids, which are looked up in `segments`.
+    fn segment_inserted(&mut self, _segment: SegmentId, _segments: &Arena) {}
+
+    /// Called after a segment was removed from the sweep line.
+    fn segment_removed(&mut self, _segment: SegmentId, _segments: &Arena) {}
+
+    /// Called after two segments swapped their order in the sweep line at an intersection point.
+    fn segments_swapped(
+        &mut self,
+        _point: &Point,
+        _segment: SegmentId,
+        _other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+    }
+
+    /// Called when an intersection of two neighbors was added to the event queue.
+    fn intersection_scheduled(
+        &mut self,
+        _point: &Point,
+        _segment: SegmentId,
+        _other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+    }
+
+    /// Called when an intersection of two neighbors was found, but not added to the event queue.
+    fn intersection_discarded(
+        &mut self,
+        _point: &Point,
+        _segment: SegmentId,
+        _other_segment: SegmentId,
+        _segments: &Arena,
+        _reason: DiscardReason,
+    ) {
+    }
+
+    /// Called after an event was fully processed, with the state of the sweep line afterwards.
+    ///
+    /// The segments of the event and the sweep line are looked up in `segments`.
+    fn event_handled(&mut self, _event: &Event, _sweep_line: &SweepLine, _segments: &Arena) {}
+}
+
+/// Observer that ignores every step, used by [`EventQueue::sweep`](crate::event_queue::EventQueue::sweep).
+pub struct NoopObserver;
+
+impl SweepObserver for NoopObserver {}
+
+/// Forwards to the observer, if there is one.
+impl<O: SweepObserver> SweepObserver for Option<O> {
+    fn event_popped(&mut self, event: &Event) {
+        if let Some(observer) = self {
+            observer.event_popped(event);
+        }
+    }
+
+    fn segment_inserted(&mut self, segment: SegmentId, segments: &Arena) {
+        if let Some(observer) = self {
+            observer.segment_inserted(segment, segments);
+        }
+    }
+
+    fn segment_removed(&mut self, segment: SegmentId, segments: &Arena) {
+        if let Some(observer) = self {
+            observer.segment_removed(segment, segments);
+        }
+    }
+
+    fn segments_swapped(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        if let Some(observer) = self {
+            observer.segments_swapped(point, segment, other_segment, segments);
+        }
+    }
+
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        if let Some(observer) = self {
+            observer.intersection_scheduled(point, segment, other_segment, segments);
+        }
+    }
+
+    fn intersection_discarded(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        if let Some(observer) = self {
+            observer.intersection_discarded(point, segment, other_segment, segments, reason);
+        }
+    }
+
+    fn event_handled(&mut self, event: &Event, sweep_line: &SweepLine, segments: &Arena) {
+        if let Some(observer) = self {
+            observer.event_handled(event, sweep_line, segments);
+        }
+    }
+}
+
+/// Forwards every step to both observers, first to `A`, then to `B`.
+impl<A: SweepObserver, B: SweepObserver> SweepObserver for (A, B) {
+    fn event_popped(&mut self, event: &Event) {
+        self.0.event_popped(event);
+        self.1.event_popped(event);
+    }
+
+    fn segment_inserted(&mut self, segment: SegmentId, segments: &Arena) {
+        self.0.segment_inserted(segment, segments);
+        self.1.segment_inserted(segment, segments);
+    }
+
+    fn segment_removed(&mut self, segment: SegmentId, segments: &Arena) {
+        self.0.segment_removed(segment, segments);
+        self.1.segment_removed(segment, segments);
+    }
+
+    fn segments_swapped(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        self.0
+            .segments_swapped(point, segment, other_segment, segments);
+        self.1
+            .segments_swapped(point, segment, other_segment, segments);
+    }
+
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+    ) {
+        self.0
+            .intersection_scheduled(point, segment, other_segment, segments);
+        self.1
+            .intersection_scheduled(point, segment, other_segment, segments);
+    }
+
+    fn intersection_discarded(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        self.0
+            .intersection_discarded(point, segment, other_segment, segments, reason);
+        self.1
+            .intersection_discarded(point, segment, other_segment, segments, reason);
+    }
+
+    fn event_handled(&mut self, event: &Event, sweep_line: &SweepLine, segments: &Arena) {
+        self.0.event_handled(event, sweep_line, segments);
+        self.1.event_handled(event, sweep_line, segments);
+    }
+}
+
+/// Remembers which two segments produced each reported intersection.
+#[derive(Debug, Default)]
+pub struct IntersectionPairs {
+    pub pairs: BTreeMap<Point, (SegmentId, SegmentId)>,
+}
+
+impl SweepObserver for IntersectionPairs {
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+        self.pairs.insert(point.clone(), (segment, other_segment));
+    }
+}
+
+/// Counts how many of the reported intersections lie on each segment, by the id of the segment.
+///
+/// Every segment through an intersection is counted once, also those which only find it again after it was
+/// scheduled by another pair.
+#[derive(Debug, Default)]
+pub struct IntersectionCounter {
+    counts: Vec<usize>,
+    /// Segments counted for every scheduled intersection the sweep line has not reached yet
+    pending: BTreeMap<Point, Vec<SegmentId>>,
+}
+
+impl IntersectionCounter {
+    fn count(&mut self, segment: SegmentId) {
+        if self.counts.len() <= segment.index() {
+            self.counts.resize(segment.index() + 1, 0);
+        }
+        self.counts[segment.index()] += 1;
+    }
+
+    /// The counts of the first `len` segments by their ids, which are their positions in the input.
+    pub fn segment_counts(&self, len: usize) -> SegmentCounts {
+        let mut counts = self.counts.clone();
+        counts.resize(len, 0);
+        SegmentCounts { counts }
+    }
+}
+
+/// Number of intersections of every segment, by the position of the segment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SegmentCounts {
+    pub counts: Vec<usize>,
+}
+
+impl SegmentCounts {
+    /// Number of segments with each number of intersections, from none up to the largest count.
+    pub fn histogram(&self) -> Vec<usize> {
+        let max = self.counts.iter().copied().max().unwrap_or_default();
+        let mut histogram = vec![0; max + 1];
+        for &count in &self.counts {
+            histogram[count] += 1;
+        }
+        histogram
+    }
+
+    /// Position and count of the `n` segments with the most intersections, the earlier segment first on ties.
+    pub fn top(&self, n: usize) -> Vec<(usize, usize)> {
+        let mut top: Vec<_> = self.counts.iter().copied().enumerate().collect();
+        top.sort_by_key(|&(id, count)| (Reverse(count), id));
+        top.truncate(n);
+        top
+    }
+}
+
+impl SweepObserver for IntersectionCounter {
+    fn event_popped(&mut self, event: &Event) {
+        // once the sweep line passes the point, it cannot be found again
+        if let Event::Intersection { point, .. } = event {
+            self.pending.remove(point);
+        }
+    }
+
+    fn intersection_scheduled(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+    ) {
+        self.count(segment);
+        self.count(other_segment);
+        self.pending
+            .insert(point.clone(), vec![segment, other_segment]);
+    }
+
+    fn intersection_discarded(
+        &mut self,
+        point: &Point,
+        segment: SegmentId,
+        other_segment: SegmentId,
+        _segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        if reason != DiscardReason::Duplicate {
+            return;
+        }
+        let Some(counted) = self.pending.get_mut(point) else {
+            return;
+        };
+        let new: Vec<_> = [segment, other_segment]
+            .into_iter()
+            .filter(|id| !counted.contains(id))
+            .collect();
+        counted.extend(&new);
+        for id in new {
+            self.count(id);
+        }
+    }
+}
+
+/// Counts the work done by the sweep, for the run statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepStats {
+    pub events: usize,
+    /// Largest number of segments in the sweep line at once
+    pub max_status_size: usize,
+    /// Intersections found again by another pair of segments
+    pub duplicates_discarded: usize,
+}
+
+impl SweepObserver for SweepStats {
+    fn intersection_discarded(
+        &mut self,
+        _point: &Point,
+        _segment: SegmentId,
+        _other_segment: SegmentId,
+        _segments: &Arena,
+        reason: DiscardReason,
+    ) {
+        if reason == DiscardReason::Duplicate {
+            self.duplicates_discarded += 1;
+        }
+    }
+
+    fn event_handled(&mut self, _event: &Event, sweep_line: &SweepLine, _segments: &Arena) {
+        self.events += 1;
+        self.max_status_size = self.max_status_size.max(sweep_line.elements.len());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{event_queue::EventQueue, geometry::Line, input::read_file};
+
+    #[derive(Default)]
+    struct CountingObserver {
+        popped: usize,
+        inserted: usize,
+        removed: usize,
+        swapped: usize,
+        scheduled: usize,
+        handled: usize,
+    }
+
+    impl SweepObserver for CountingObserver {
+        fn event_popped(&mut self, _event: &Event) {
+            self.popped += 1;
+        }
+
+        fn segment_inserted(&mut self, _segment: SegmentId, _segments: &Arena) {
+            self.inserted += 1;
+        }
+
+        fn segment_removed(&mut self, _segment: SegmentId, _segments: &Arena) {
+            self.removed += 1;
+        }
+
+        fn segments_swapped(
+            &mut self,
+            _point: &Point,
+            _segment: SegmentId,
+            _other_segment: SegmentId,
+            _segments: &Arena,
+        ) {
+            self.swapped += 1;
+        }
+
+        fn intersection_scheduled(
+            &mut self,
+            _point: &Point,
+            _segment: SegmentId,
+            _other_segment: SegmentId,
+            _segments: &Arena,
+        ) {
+            self.scheduled += 1;
+        }
+
+        fn event_handled(&mut self, _event: &Event, _sweep_line: &SweepLine, _segments: &Arena) {
+            self.handled += 1;
+        }
+    }
+
+    #[test]
+    fn test_observer_sees_every_step() {
+        let l1 = Line::from_str("0 1 5 1").unwrap();
+        let l2 = Line::from_str("1.5 2.5 4 0.5").unwrap();
+        let l3 = Line::from_str("0.5 1.5 4 2.5").unwrap();
+
+        let mut observer = CountingObserver::default();
+        let queue = EventQueue::new(vec![l1, l2, l3]);
+        let intersections = queue.sweep_with_observer(&mut observer);
+
+        assert_eq!(intersections.len(), 2);
+        assert_eq!(observer.inserted, 3);
+        assert_eq!(observer.removed, 3);
+        assert_eq!(observer.swapped, 2);
+        assert_eq!(observer.scheduled, 2);
+        assert_eq!(observer.popped, 8);
+        assert_eq!(observer.handled, 8);
+    }
+
+    #[test]
+    fn test_intersection_counter() {
+        let l1 = Line::from_str("0 1 5 1").unwrap();
+        let l2 = Line::from_str("1.5 2.5 4 0.5").unwrap();
+        let l3 = Line::from_str("0.5 1.5 4 2.5").unwrap();
+
+        let mut counter = IntersectionCounter::default();
+        EventQueue::new(vec![l1, l2, l3]).sweep_with_observer(&mut counter);
+        assert_eq!(counter.segment_counts(3).counts, [1, 2, 1]);
+
+        // the third segment through the point only finds it again
+        let lines = ["0 0 4 4", "0 4 4 0", "0 2 4 2"].map(|s| Line::from_str(s).unwrap());
+        let mut counter = IntersectionCounter::default();
+        let points = EventQueue::new(lines.to_vec()).sweep_with_observer(&mut counter);
+        assert_eq!(points.len(), 1);
+        assert_eq!(counter.segment_counts(3).counts, [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_segment_counts() {
+        let lines: Vec<_> = ["0 1 5 1", "1.5 2.5 4 0.5", "0.5 1.5 4 2.5", "0 3 1 3"]
+            .iter()
+            .map(|s| Line::from_str(s).unwrap())
+            .collect();
+
+        let mut counter = IntersectionCounter::default();
+        let count = EventQueue::new(lines.clone()).count_with_observer(&mut counter);
+        assert_eq!(count, 2);
+
+        let counts = counter.segment_counts(lines.len());
+        assert_eq!(counts.counts, [1, 2, 1, 0]);
+        assert_eq!(counts.histogram(), [1, 2, 1]);
+        assert_eq!(counts.top(2), [(1, 2), (0, 1)]);
+        assert_eq!(SegmentCounts::default().histogram(), [0]);
+
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        assert_eq!(EventQueue::new(lines).count(), 796);
+    }
+
+    #[test]
+    fn test_sweep_stats() {
+        // l3 separates l1 and l2 for a while, which find their intersection again once it ends
+        let l1 = Line::from_str("0 0 20 10").unwrap();
+        let l2 = Line::from_str("0 10 20 0").unwrap();
+        let l3 = Line::from_str("2 4 3 4.2").unwrap();
+
+        let mut stats = SweepStats::default();
+        let points = EventQueue::new(vec![l1, l2, l3]).sweep_with_observer(&mut stats);
+
+        assert_eq!(points.len(), 1);
+        assert_eq!(stats.events, 7);
+        assert_eq!(stats.max_status_size, 3);
+        assert_eq!(stats.duplicates_discarded, 1);
+    }
+}
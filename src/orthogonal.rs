@@ -0,0 +1,217 @@
+//! Sweep for axis-parallel segments, in O(n log n + k) for n segments and k intersections.
+//!
+//! Unlike the general sweep, vertical segments and shared end points are allowed. Horizontal
+//! segments are kept in an ordered map by their y while the sweep passes them, and every vertical
+//! segment is answered as a range query on that map.
+
+use std::{cmp::Ordering, collections::BTreeMap, collections::BTreeSet};
+
+use crate::{
+    arena::SegmentId,
+    geometry::{Line, Point},
+};
+
+/// Whether the segment is horizontal or vertical, which includes segments of length zero.
+pub fn is_axis_parallel(line: &Line) -> bool {
+    line.p.x == line.q.x || line.p.y == line.q.y
+}
+
+/// Coordinate ordered by `f64::total_cmp`, with `-0.0` normalized to `0.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Coordinate(f64);
+
+impl Coordinate {
+    fn new(value: f64) -> Self {
+        Coordinate(value + 0.0)
+    }
+}
+
+impl Eq for Coordinate {}
+
+impl Ord for Coordinate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Coordinate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// At the same x, horizontal segments are inserted before and removed after the vertical queries,
+/// so touching end points are reported like by [`Line::intersection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EventKind {
+    Insert,
+    Query,
+    Remove,
+}
+
+/// Crossings of horizontal and vertical segments, rounded to 9 decimals like in the general sweep.
+///
+/// Panics if a segment is not axis-parallel. Parallel segments which overlap or touch do not intersect in a single point
+/// and are not reported; a segment of length zero is treated as a vertical one.
+pub fn intersections(lines: &[Line]) -> BTreeSet<Point> {
+    let mut intersections = BTreeSet::new();
+    sweep(lines, |point, _, _| {
+        intersections.insert(point);
+    });
+    intersections
+}
+
+/// Like [`intersections`], with the ids of all segments through each point in ascending order, which are their
+/// positions in `lines`.
+pub fn crossings(lines: &[Line]) -> BTreeMap<Point, Vec<SegmentId>> {
+    let mut crossings: BTreeMap<Point, Vec<SegmentId>> = BTreeMap::new();
+    sweep(lines, |point, vertical, horizontal| {
+        let segments = crossings.entry(point).or_default();
+        segments.push(SegmentId::new(vertical));
+        segments.extend(horizontal.iter().copied().map(SegmentId::new));
+    });
+    for segments in crossings.values_mut() {
+        segments.sort_unstable();
+        segments.dedup();
+    }
+    crossings
+}
+
+/// Calls `report` with the rounded point, the vertical segment and the horizontal segments of every crossing.
+fn sweep(lines: &[Line], mut report: impl FnMut(Point, usize, &[usize])) {
+    let mut events = Vec::with_capacity(2 * lines.len());
+    for (id, line) in lines.iter().enumerate() {
+        let (min, max) = if line.p <= line.q {
+            (&line.p, &line.q)
+        } else {
+            (&line.q, &line.p)
+        };
+        if line.p.x == line.q.x {
+            events.push((Coordinate::new(min.x), EventKind::Query, id));
+        } else if line.p.y == line.q.y {
+            events.push((Coordinate::new(min.x), EventKind::Insert, id));
+            events.push((Coordinate::new(max.x), EventKind::Remove, id));
+        } else {
+            panic!("Segment is not axis-parallel: {}", line);
+        }
+    }
+    events.sort_by_key(|&(x, kind, _)| (x, kind));
+
+    // horizontal segments crossing the sweep line, by y
+    let mut status: BTreeMap<Coordinate, Vec<usize>> = BTreeMap::new();
+    for (x, kind, id) in events {
+        let line = &lines[id];
+        let y = Coordinate::new(line.p.y);
+        match kind {
+            EventKind::Insert => status.entry(y).or_default().push(id),
+            EventKind::Remove => {
+                if let Some(segments) = status.get_mut(&y) {
+                    segments.retain(|&segment| segment != id);
+                    if segments.is_empty() {
+                        status.remove(&y);
+                    }
+                }
+            }
+            EventKind::Query => {
+                let from = Coordinate::new(line.p.y.min(line.q.y));
+                let to = Coordinate::new(line.p.y.max(line.q.y));
+                for (y, horizontal) in status.range(from..=to) {
+                    let point = Point { x: x.0, y: y.0 };
+                    report(point.round(9), id, horizontal);
+                }
+            }
+        }
+    }
+}
+
+/// Tests every horizontal segment against every vertical one in O(n²), the reference to check [`intersections`] against.
+///
+/// Unlike [`brute_force::intersections`](crate::brute_force::intersections), segments on the same line are skipped instead of panicking.
+pub fn brute_force(lines: &[Line]) -> BTreeSet<Point> {
+    let (vertical, horizontal): (Vec<_>, Vec<_>) =
+        lines.iter().partition(|line| line.p.x == line.q.x);
+    let mut intersections = BTreeSet::new();
+    for line in &horizontal {
+        for other_line in &vertical {
+            if let Some(point) = line.intersection(other_line) {
+                intersections.insert(point.round(9));
+            }
+        }
+    }
+    intersections
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{brute_force, generate::Rng};
+
+    fn lines(s: &[&str]) -> Vec<Line> {
+        s.iter().map(|line| Line::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_crossings() {
+        // the fourth segment touches the first with its end point, the fifth shares an end point
+        // with the second and the last overlaps the first, which is not a single point
+        let lines = lines(&[
+            "0 1 4 1", "2 0 2 3", "3 3 3 -1", "1 1 1 2", "2 3 5 3", "3 1 6 1",
+        ]);
+        let found = intersections(&lines);
+        let expected: BTreeSet<_> = [(1.0, 1.0), (2.0, 1.0), (2.0, 3.0), (3.0, 1.0), (3.0, 3.0)]
+            .into_iter()
+            .map(|(x, y)| Point { x, y })
+            .collect();
+        assert_eq!(found, expected);
+        let crossings = crossings(&lines);
+        assert!(crossings.keys().eq(&expected));
+        assert_eq!(
+            crossings[&Point { x: 2.0, y: 3.0 }],
+            [1, 4].map(SegmentId::new)
+        );
+        assert_eq!(
+            crossings[&Point { x: 3.0, y: 1.0 }],
+            [0, 2, 5].map(SegmentId::new)
+        );
+        assert!(is_axis_parallel(&lines[1]));
+        assert!(!is_axis_parallel(&Line::from_str("0 0 1 1").unwrap()));
+    }
+
+    #[test]
+    fn test_same_as_brute_force() {
+        // a grid of integer coordinates, with many shared end points and touching segments
+        let mut rng = Rng::new(7);
+        let mut coordinate = || (rng.next_u64() % 50) as f64;
+        let mut lines = Vec::new();
+        for index in 0..400 {
+            let (a, b, c) = (coordinate(), coordinate(), coordinate());
+            if b == c {
+                continue;
+            }
+            let line = if index % 2 == 0 {
+                format!("{} {} {} {}", b, a + 0.5, c, a + 0.5)
+            } else {
+                format!("{} {} {} {}", a, b, a, c)
+            };
+            lines.push(Line::from_str(&line).unwrap());
+        }
+
+        let expected = brute_force(&lines);
+        assert!(expected.len() > 1000);
+        assert_eq!(intersections(&lines), expected);
+
+        let crossing: Vec<_> = lines.iter().take(2).cloned().collect();
+        assert_eq!(
+            intersections(&crossing),
+            brute_force::intersections(&crossing)
+        );
+    }
+
+    #[test]
+    fn test_negative_zero() {
+        let lines = lines(&["-1 -0 1 -0", "0 0 0 1"]);
+        assert_eq!(intersections(&lines).len(), 1);
+    }
+}
@@ -0,0 +1,786 @@
+use std::{
+    io::{self, Write},
+    str::FromStr,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    algorithm::Intersections,
+    binary, float,
+    geometry::{self, IntersectionParameters, Line, Point},
+    observer::SegmentCounts,
+    polyline::{Crossing, Crossings, Location},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `x y` per line, followed by the features of both segments as quoted strings if known
+    #[default]
+    Txt,
+    Csv,
+    Json,
+    /// A `FeatureCollection` of points
+    GeoJson,
+    /// Only the points, see [`binary::write_intersections`]
+    Binary,
+}
+
+impl OutputFormat {
+    /// Suffix appended to the input file name.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Txt => "i",
+            OutputFormat::Csv => "i.csv",
+            OutputFormat::Json => "i.json",
+            OutputFormat::GeoJson => "i.geojson",
+            OutputFormat::Binary => "i.bin",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "txt" => Ok(OutputFormat::Txt),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "geojson" => Ok(OutputFormat::GeoJson),
+            "binary" => Ok(OutputFormat::Binary),
+            _ => Err(format!("Unknown output format: {}", s)),
+        }
+    }
+}
+
+/// How numbers are written to the text based formats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Shortest decimal representation which parses back to the same value
+    #[default]
+    Decimal,
+    /// Hexadecimal floats like `0x1.8p+1`, see [`float::to_hex`]; strings in JSON
+    Hex,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Add the coordinates of both segments to the structured formats
+    pub with_segments: bool,
+    /// Add the position along both segments, the crossing angle and the orientation to the structured formats, see
+    /// [`Line::intersection_parameters`]
+    pub with_parameters: bool,
+    pub floats: FloatFormat,
+    /// Round to this many decimals for human readable output, the values then no longer round-trip
+    pub round: Option<u32>,
+    /// Recompute every intersection from its segments instead of writing it rounded to 9 decimals like the sweep,
+    /// see [`write_result`]
+    pub exact: bool,
+}
+
+impl WriteOptions {
+    fn value(&self, value: f64) -> f64 {
+        match self.round {
+            Some(decimals) => geometry::round(value, decimals),
+            None => value,
+        }
+    }
+
+    pub fn number(&self, value: f64) -> String {
+        match self.floats {
+            FloatFormat::Decimal => self.value(value).to_string(),
+            FloatFormat::Hex => float::to_hex(self.value(value)),
+        }
+    }
+
+    fn json_number(&self, value: f64) -> Value {
+        match self.floats {
+            FloatFormat::Decimal => json!(self.value(value)),
+            FloatFormat::Hex => json!(float::to_hex(self.value(value))),
+        }
+    }
+
+    /// `x y`, like the `Display` implementation of [`Point`].
+    pub fn point(&self, point: &Point) -> String {
+        format!("{} {}", self.number(point.x), self.number(point.y))
+    }
+}
+
+/// One of the two segments of an intersection.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentInfo<'a> {
+    /// Position of the segment in the input, starting at 0
+    pub id: usize,
+    pub line: &'a Line,
+    /// Feature of WKT or GeoJSON input
+    pub feature: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IntersectionRecord<'a> {
+    pub point: Point,
+    pub segments: [SegmentInfo<'a>; 2],
+}
+
+impl IntersectionRecord<'_> {
+    /// Parameters of the intersection of both segments, seen from the first one, if requested.
+    fn parameters(&self, options: &WriteOptions) -> Option<IntersectionParameters> {
+        let [a, b] = self.segments;
+        options
+            .with_parameters
+            .then(|| a.line.intersection_parameters(b.line))
+            .flatten()
+    }
+}
+
+/// Writes the intersections in the given format.
+pub fn write_intersections<'a, W: Write>(
+    writer: &mut W,
+    format: OutputFormat,
+    records: impl IntoIterator<Item = IntersectionRecord<'a>>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let properties = |record: IntersectionRecord| {
+        let properties = record_json(&record, options);
+        (record.point, properties)
+    };
+    match format {
+        OutputFormat::Txt => write_txt(writer, records, options),
+        OutputFormat::Csv => write_csv(writer, records, options),
+        OutputFormat::Json => write_json(writer, records.into_iter().map(properties), options),
+        OutputFormat::GeoJson => {
+            write_geojson(writer, records.into_iter().map(properties), options)
+        }
+        OutputFormat::Binary => {
+            let points: Vec<_> = records.into_iter().map(|r| r.point).collect();
+            binary::write_intersections(writer, &points)
+        }
+    }
+}
+
+/// Writes the result of [`run`](crate::algorithm::run) in the given format, plain `x y` lines if the segments are
+/// not known.
+///
+/// [`WriteOptions::exact`] needs the pairs of segments, without them the points are written as found.
+pub fn write_result<W: Write>(
+    writer: &mut W,
+    format: OutputFormat,
+    intersections: &Intersections,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let segments = &intersections.segments;
+    let pairs = intersections.pairs.as_ref();
+    let point = |point: &Point| match pairs.filter(|_| options.exact) {
+        Some(pairs) => {
+            let [id, other_id] = pairs[point];
+            segments.lines[id.index()]
+                .intersection(&segments.lines[other_id.index()])
+                .unwrap_or_else(|| point.clone())
+        }
+        None => point.clone(),
+    };
+
+    let points = &intersections.points;
+    if format == OutputFormat::Binary {
+        let points: Vec<_> = points.iter().map(point).collect();
+        return binary::write_intersections(writer, &points);
+    }
+    let (Some(pairs), false) = (pairs, segments.lines.is_empty()) else {
+        return write_points(writer, points.iter().map(point), options);
+    };
+    let records = points.iter().map(|intersection| IntersectionRecord {
+        point: point(intersection),
+        segments: pairs[intersection].map(|id| segments.info(id.index())),
+    });
+    write_intersections(writer, format, records, options)
+}
+
+/// Writes one `x y` line per point, for intersections whose segments are not known.
+pub fn write_points<W: Write>(
+    writer: &mut W,
+    points: impl IntoIterator<Item = Point>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    for point in points {
+        writeln!(writer, "{}", options.point(&point))?;
+    }
+    Ok(())
+}
+
+/// Writes the crossings of polylines in the given format, the crossings between polylines before the self-crossings.
+pub fn write_crossings<W: Write>(
+    writer: &mut W,
+    format: OutputFormat,
+    crossings: &Crossings,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let all = || crossings.between.iter().chain(&crossings.self_crossings);
+    let properties =
+        |crossing: &Crossing| (crossing.point.clone(), crossing_json(crossing, options));
+    match format {
+        OutputFormat::Txt => write_crossings_txt(writer, crossings, options),
+        OutputFormat::Csv => {
+            writeln!(
+                writer,
+                "x,y,polyline_a,segment_a,t_a,polyline_b,segment_b,t_b,self_crossing"
+            )?;
+            for crossing in all() {
+                let (a, b) = (&crossing.a, &crossing.b);
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{},{}",
+                    options.number(crossing.point.x),
+                    options.number(crossing.point.y),
+                    a.polyline,
+                    a.segment,
+                    options.number(a.t),
+                    b.polyline,
+                    b.segment,
+                    options.number(b.t),
+                    crossing.is_self_crossing()
+                )?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => write_json(writer, all().map(properties), options),
+        OutputFormat::GeoJson => write_geojson(writer, all().map(properties), options),
+        OutputFormat::Binary => {
+            let points: Vec<_> = crossings.points().into_iter().collect();
+            binary::write_intersections(writer, &points)
+        }
+    }
+}
+
+/// Both locations of a crossing, and whether the polyline crosses itself.
+fn crossing_json(crossing: &Crossing, options: &WriteOptions) -> Map<String, Value> {
+    let location = |location: &Location| {
+        json!({
+            "polyline": location.polyline,
+            "segment": location.segment,
+            "t": options.json_number(location.t),
+        })
+    };
+    let mut object = Map::new();
+    object.insert(
+        "locations".to_string(),
+        json!([location(&crossing.a), location(&crossing.b)]),
+    );
+    object.insert(
+        "self_crossing".to_string(),
+        json!(crossing.is_self_crossing()),
+    );
+    object
+}
+
+/// Writes the crossings of polylines as text, in two sections for the crossings between polylines and the
+/// self-crossings, each with a header comment naming the columns.
+fn write_crossings_txt<W: Write>(
+    writer: &mut W,
+    crossings: &Crossings,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "# crossings between polylines: x y polyline segment t other_polyline other_segment other_t"
+    )?;
+    for crossing in &crossings.between {
+        let (a, b) = (&crossing.a, &crossing.b);
+        writeln!(
+            writer,
+            "{} {} {} {} {} {} {}",
+            options.point(&crossing.point),
+            a.polyline,
+            a.segment,
+            options.number(a.t),
+            b.polyline,
+            b.segment,
+            options.number(b.t)
+        )?;
+    }
+    writeln!(
+        writer,
+        "# self-crossings: x y polyline segment t other_segment other_t"
+    )?;
+    for crossing in &crossings.self_crossings {
+        let (a, b) = (&crossing.a, &crossing.b);
+        writeln!(
+            writer,
+            "{} {} {} {} {} {}",
+            options.point(&crossing.point),
+            a.polyline,
+            a.segment,
+            options.number(a.t),
+            b.segment,
+            options.number(b.t)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the histogram of the intersection counts, the `top` segments with the most intersections and the count of
+/// every segment as text, in three sections with a header comment naming the columns.
+pub fn write_segment_counts<W: Write>(
+    writer: &mut W,
+    counts: &SegmentCounts,
+    top: usize,
+) -> io::Result<()> {
+    writeln!(writer, "# histogram: intersections segments")?;
+    for (intersections, segments) in counts.histogram().into_iter().enumerate() {
+        if segments > 0 {
+            writeln!(writer, "{} {}", intersections, segments)?;
+        }
+    }
+    writeln!(writer, "# top {}: segment intersections", top)?;
+    for (segment, intersections) in counts.top(top) {
+        writeln!(writer, "{} {}", segment, intersections)?;
+    }
+    writeln!(writer, "# segments: segment intersections")?;
+    for (segment, intersections) in counts.counts.iter().enumerate() {
+        writeln!(writer, "{} {}", segment, intersections)?;
+    }
+    Ok(())
+}
+
+fn write_txt<'a, W: Write>(
+    writer: &mut W,
+    records: impl IntoIterator<Item = IntersectionRecord<'a>>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    for record in records {
+        let point = options.point(&record.point);
+        match record.segments.map(|s| s.feature) {
+            // quoted like JSON strings, feature ids may contain spaces or look like numbers
+            [Some(feature), Some(other_feature)] => writeln!(
+                writer,
+                "{} {} {}",
+                point,
+                json!(feature),
+                json!(other_feature)
+            )?,
+            _ => writeln!(writer, "{}", point)?,
+        }
+    }
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv<'a, W: Write>(
+    writer: &mut W,
+    records: impl IntoIterator<Item = IntersectionRecord<'a>>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let mut records = records.into_iter().peekable();
+    let with_features = records
+        .peek()
+        .is_some_and(|r| r.segments[0].feature.is_some());
+
+    write!(writer, "x,y,segment_a,segment_b")?;
+    if with_features {
+        write!(writer, ",feature_a,feature_b")?;
+    }
+    if options.with_segments {
+        write!(writer, ",a_x1,a_y1,a_x2,a_y2,b_x1,b_y1,b_x2,b_y2")?;
+    }
+    if options.with_parameters {
+        write!(writer, ",t_a,t_b,angle,orientation")?;
+    }
+    writeln!(writer)?;
+
+    for record in records {
+        let parameters = record.parameters(options);
+        let [a, b] = record.segments;
+        write!(
+            writer,
+            "{},{},{},{}",
+            options.number(record.point.x),
+            options.number(record.point.y),
+            a.id,
+            b.id
+        )?;
+        if with_features {
+            write!(
+                writer,
+                ",{},{}",
+                csv_field(a.feature.unwrap_or_default()),
+                csv_field(b.feature.unwrap_or_default())
+            )?;
+        }
+        if options.with_segments {
+            for line in [a.line, b.line] {
+                for value in [line.p.x, line.p.y, line.q.x, line.q.y] {
+                    write!(writer, ",{}", options.number(value))?;
+                }
+            }
+        }
+        match parameters {
+            Some(parameters) => write!(
+                writer,
+                ",{},{},{},{}",
+                options.number(parameters.t),
+                options.number(parameters.other_t),
+                options.number(parameters.angle),
+                parameters.orientation
+            )?,
+            // the segments of the intersection barely miss each other when recomputed
+            None if options.with_parameters => write!(writer, ",,,,")?,
+            None => {}
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn segment_json(segment: &SegmentInfo, t: Option<f64>, options: &WriteOptions) -> Value {
+    let mut object = Map::new();
+    object.insert("id".to_string(), json!(segment.id));
+    if let Some(feature) = segment.feature {
+        object.insert("feature".to_string(), json!(feature));
+    }
+    if let Some(t) = t {
+        object.insert("t".to_string(), options.json_number(t));
+    }
+    if options.with_segments {
+        let line = segment.line;
+        let point = |p: &Point| json!([options.json_number(p.x), options.json_number(p.y)]);
+        object.insert(
+            "coordinates".to_string(),
+            json!([point(&line.p), point(&line.q)]),
+        );
+    }
+    Value::Object(object)
+}
+
+/// The segments of an intersection, with their parameters if requested.
+fn record_json(record: &IntersectionRecord, options: &WriteOptions) -> Map<String, Value> {
+    let parameters = record.parameters(options);
+    let [a, b] = &record.segments;
+    let mut object = Map::new();
+    object.insert(
+        "segments".to_string(),
+        json!([
+            segment_json(a, parameters.map(|p| p.t), options),
+            segment_json(b, parameters.map(|p| p.other_t), options),
+        ]),
+    );
+    if let Some(parameters) = parameters {
+        object.insert("angle".to_string(), options.json_number(parameters.angle));
+        object.insert(
+            "orientation".to_string(),
+            json!(parameters.orientation.to_string()),
+        );
+    }
+    object
+}
+
+/// Writes an array of objects with the coordinates of each point and its properties.
+fn write_json<W: Write>(
+    writer: &mut W,
+    points: impl IntoIterator<Item = (Point, Map<String, Value>)>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    write!(writer, "[")?;
+    for (index, (point, properties)) in points.into_iter().enumerate() {
+        if index > 0 {
+            write!(writer, ",")?;
+        }
+        let mut value = Map::new();
+        value.insert("x".to_string(), options.json_number(point.x));
+        value.insert("y".to_string(), options.json_number(point.y));
+        value.extend(properties);
+        write!(writer, "\n  ")?;
+        serde_json::to_writer(&mut *writer, &value)?;
+    }
+    writeln!(writer, "\n]")
+}
+
+/// Writes a `FeatureCollection` with a point feature and its properties for each point.
+fn write_geojson<W: Write>(
+    writer: &mut W,
+    points: impl IntoIterator<Item = (Point, Map<String, Value>)>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    write!(writer, r#"{{"type":"FeatureCollection","features":["#)?;
+    for (index, (point, properties)) in points.into_iter().enumerate() {
+        if index > 0 {
+            write!(writer, ",")?;
+        }
+        let value = json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [options.json_number(point.x), options.json_number(point.y)],
+            },
+            "properties": properties,
+        });
+        write!(writer, "\n  ")?;
+        serde_json::to_writer(&mut *writer, &value)?;
+    }
+    writeln!(writer, "\n]}}")
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{
+        algorithm::{self, RunOptions},
+        collect,
+        input::{InputFormat, SegmentReader},
+        polyline::{self, Polyline},
+    };
+
+    fn write(format: OutputFormat, features: bool, with_segments: bool) -> String {
+        let options = WriteOptions {
+            with_segments,
+            ..Default::default()
+        };
+        write_with(format, features, &options)
+    }
+
+    fn write_with(format: OutputFormat, features: bool, options: &WriteOptions) -> String {
+        let a = Line::from_str("0 1 5 1").unwrap();
+        let b = Line::from_str("1 0 4 2").unwrap();
+        let record = IntersectionRecord {
+            point: Point { x: 2.5, y: 1.0 },
+            segments: [
+                SegmentInfo {
+                    id: 0,
+                    line: &a,
+                    feature: features.then_some("road, north"),
+                },
+                SegmentInfo {
+                    id: 1,
+                    line: &b,
+                    feature: features.then_some("river"),
+                },
+            ],
+        };
+
+        let mut out = Vec::new();
+        write_intersections(&mut out, format, [record], options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_txt() {
+        assert_eq!(write(OutputFormat::Txt, false, true), "2.5 1\n");
+        assert_eq!(
+            write(OutputFormat::Txt, true, false),
+            "2.5 1 \"road, north\" \"river\"\n"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            write(OutputFormat::Csv, false, false),
+            "x,y,segment_a,segment_b\n2.5,1,0,1\n"
+        );
+        assert_eq!(
+            write(OutputFormat::Csv, true, true),
+            "x,y,segment_a,segment_b,feature_a,feature_b,a_x1,a_y1,a_x2,a_y2,b_x1,b_y1,b_x2,b_y2\n\
+             2.5,1,0,1,\"road, north\",river,0,1,5,1,1,0,4,2\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let value: Value = serde_json::from_str(&write(OutputFormat::Json, true, true)).unwrap();
+        assert_eq!(value[0]["x"], json!(2.5));
+        assert_eq!(value[0]["segments"][0]["feature"], json!("road, north"));
+        assert_eq!(
+            value[0]["segments"][1]["coordinates"],
+            json!([[1.0, 0.0], [4.0, 2.0]])
+        );
+
+        let value: Value = serde_json::from_str(&write(OutputFormat::Json, false, false)).unwrap();
+        assert_eq!(value[0]["segments"][1], json!({"id": 1}));
+    }
+
+    #[test]
+    fn test_geojson() {
+        let value: Value =
+            serde_json::from_str(&write(OutputFormat::GeoJson, false, false)).unwrap();
+        assert_eq!(value["type"], json!("FeatureCollection"));
+        assert_eq!(
+            value["features"][0]["geometry"]["coordinates"],
+            json!([2.5, 1.0])
+        );
+        assert_eq!(
+            value["features"][0]["properties"]["segments"][0]["id"],
+            json!(0)
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let options = WriteOptions {
+            with_parameters: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            write_with(OutputFormat::Csv, false, &options),
+            "x,y,segment_a,segment_b,t_a,t_b,angle,orientation\n2.5,1,0,1,0.5,0.5,33.690067525979785,right-to-left\n"
+        );
+        let value: Value =
+            serde_json::from_str(&write_with(OutputFormat::GeoJson, false, &options)).unwrap();
+        let properties = &value["features"][0]["properties"];
+        assert_eq!(properties["segments"][1]["t"], json!(0.5));
+        assert_eq!(properties["orientation"], json!("right-to-left"));
+        let value: Value =
+            serde_json::from_str(&write_with(OutputFormat::Json, false, &options)).unwrap();
+        assert_eq!(value[0]["x"], json!(2.5));
+        assert_eq!(value[0]["segments"][0]["t"], json!(0.5));
+    }
+
+    #[test]
+    fn test_write_result() {
+        let text = "0 0 3 1\n0 1 7 0\n";
+        let lines = ["0 0 3 1", "0 1 7 0"].map(|line| Line::from_str(line).unwrap());
+        let run = |pairs| {
+            let options = RunOptions {
+                pairs,
+                ..Default::default()
+            };
+            let input = collect::read(
+                SegmentReader::new("test", text.as_bytes()),
+                InputFormat::Segments,
+                options.collect_options(false),
+            )
+            .unwrap();
+            algorithm::run(input, &options).unwrap()
+        };
+        let write = |intersections: &Intersections, format, options: &WriteOptions| {
+            let mut out = Vec::new();
+            write_result(&mut out, format, intersections, options).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let options = WriteOptions::default();
+
+        // without the pairs only the points are known
+        assert_eq!(write(&run(false), OutputFormat::Csv, &options), "2.1 0.7\n");
+        let traced = run(true);
+        assert_eq!(
+            write(&traced, OutputFormat::Csv, &options),
+            "x,y,segment_a,segment_b\n2.1,0.7,1,0\n"
+        );
+        let exact = WriteOptions {
+            exact: true,
+            ..options
+        };
+        let point = lines[0].intersection(&lines[1]).unwrap();
+        assert_eq!(
+            write(&traced, OutputFormat::Txt, &exact),
+            format!("{}\n", point)
+        );
+        let mut out = Vec::new();
+        write_result(&mut out, OutputFormat::Binary, &traced, &options).unwrap();
+        assert_eq!(
+            binary::read_intersections(out.as_slice()).unwrap(),
+            [Point { x: 2.1, y: 0.7 }]
+        );
+    }
+
+    #[test]
+    fn test_crossings() {
+        let polylines: Vec<Polyline> = ["0 0 2 2 2 0 0 2", "1.5 -1 1.5 1"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let crossings = polyline::crossings(&polylines).unwrap();
+        let write = |format| {
+            let mut out = Vec::new();
+            write_crossings(&mut out, format, &crossings, &Default::default()).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            write(OutputFormat::Txt),
+            "# crossings between polylines: x y polyline segment t other_polyline other_segment other_t\n\
+             1.5 0.5 0 2 0.25 1 0 0.75\n\
+             # self-crossings: x y polyline segment t other_segment other_t\n\
+             1 1 0 0 0.5 2 0.5\n"
+        );
+        assert_eq!(
+            write(OutputFormat::Csv),
+            "x,y,polyline_a,segment_a,t_a,polyline_b,segment_b,t_b,self_crossing\n\
+             1.5,0.5,0,2,0.25,1,0,0.75,false\n\
+             1,1,0,0,0.5,0,2,0.5,true\n"
+        );
+        let value: Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+        assert_eq!(value[1]["x"], json!(1.0));
+        assert_eq!(value[1]["self_crossing"], json!(true));
+        assert_eq!(
+            value[0]["locations"][1],
+            json!({"polyline": 1, "segment": 0, "t": 0.75})
+        );
+        let value: Value = serde_json::from_str(&write(OutputFormat::GeoJson)).unwrap();
+        assert_eq!(
+            value["features"][0]["geometry"]["coordinates"],
+            json!([1.5, 0.5])
+        );
+        assert_eq!(
+            value["features"][0]["properties"]["locations"][0]["segment"],
+            json!(2)
+        );
+    }
+
+    #[test]
+    fn test_segment_counts() {
+        let counts = SegmentCounts {
+            counts: vec![1, 0, 3, 1],
+        };
+        let mut out = Vec::new();
+        write_segment_counts(&mut out, &counts, 2).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "# histogram: intersections segments\n0 1\n1 2\n3 1\n\
+             # top 2: segment intersections\n2 3\n0 1\n\
+             # segments: segment intersections\n0 1\n1 0\n2 3\n3 1\n"
+        );
+    }
+
+    #[test]
+    fn test_float_format() {
+        let hex = WriteOptions {
+            floats: FloatFormat::Hex,
+            ..Default::default()
+        };
+        assert_eq!(
+            write_with(OutputFormat::Txt, false, &hex),
+            "0x1.4p+1 0x1p+0\n"
+        );
+        let value: Value =
+            serde_json::from_str(&write_with(OutputFormat::Json, false, &hex)).unwrap();
+        assert_eq!(value[0]["x"], json!("0x1.4p+1"));
+
+        let rounded = WriteOptions {
+            round: Some(2),
+            ..Default::default()
+        };
+        let point = Point {
+            x: 0.1 + 0.2,
+            y: 2.0 / 3.0,
+        };
+        assert_eq!(rounded.point(&point), "0.3 0.67");
+        assert_eq!(
+            WriteOptions::default().point(&point),
+            "0.30000000000000004 0.6666666666666666"
+        );
+
+        let mut out = Vec::new();
+        write_points(&mut out, [point], &hex).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0x1.3333333333334p-2 0x1.5555555555555p-1\n"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Parallel sweep, which cuts the plane into vertical slabs and sweeps every slab on its own thread.
+//!
+//! Every slab is swept with the original segments instead of clipped copies, so the intersections
+//! are computed exactly like in the sequential sweep and the merged result is the same.
+
+use std::{collections::BTreeSet, iter, panic, thread};
+
+use crate::{
+    event_queue::EventQueue,
+    geometry::{Line, Point},
+};
+
+/// x coordinates which cut the segments into at most `slabs` slabs with about the same number of begin and end events.
+///
+/// The bounds are end points of segments, so intersections rarely lie exactly on a bound.
+pub fn slab_bounds(lines: &[Line], slabs: usize) -> Vec<f64> {
+    let mut xs: Vec<_> = lines.iter().flat_map(|line| [line.p.x, line.q.x]).collect();
+    if xs.is_empty() {
+        return Vec::new();
+    }
+    xs.sort_by(f64::total_cmp);
+
+    let mut bounds: Vec<_> = (1..slabs).map(|i| xs[i * xs.len() / slabs]).collect();
+    bounds.dedup();
+    bounds
+}
+
+/// Same result as [`EventQueue::sweep`], computed in `slabs` slabs on parallel threads.
+///
+/// Segments spanning several slabs are swept in each of them, so long segments make the slabs more expensive.
+pub fn sweep(lines: &[Line], slabs: usize) -> BTreeSet<Point> {
+    let bounds = slab_bounds(lines, slabs);
+    let from = iter::once(f64::NEG_INFINITY).chain(bounds.iter().copied());
+    let to = bounds.iter().copied().chain(iter::once(f64::INFINITY));
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = from
+            .zip(to)
+            .map(|(from, to)| scope.spawn(move || EventQueue::sweep_slab(lines, from, to)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                // a failing slab fails the whole sweep, like the sequential sweep would
+                handle
+                    .join()
+                    .unwrap_or_else(|error| panic::resume_unwind(error))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        generate::{generate, Distribution, GenerateOptions},
+        input::read_file,
+    };
+
+    #[test]
+    fn test_slab_bounds() {
+        let lines = read_file("data/s_1000_10.dat").unwrap();
+        let bounds = slab_bounds(&lines, 4);
+        assert_eq!(bounds.len(), 3);
+        assert!(bounds.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // every slab holds about a quarter of the end points
+        let x: Vec<_> = lines.iter().flat_map(|line| [line.p.x, line.q.x]).collect();
+        let below = |bound: f64| x.iter().filter(|&&x| x < bound).count();
+        for (i, bound) in bounds.iter().enumerate() {
+            assert_eq!(below(*bound), (i + 1) * x.len() / 4);
+        }
+
+        assert!(slab_bounds(&lines, 1).is_empty());
+        assert!(slab_bounds(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_same_as_sequential() {
+        let mut inputs = vec![read_file("data/s_1000_10.dat").unwrap()];
+        for distribution in [Distribution::Uniform, Distribution::Grid] {
+            inputs.push(
+                generate(&GenerateOptions {
+                    distribution,
+                    count: 300,
+                    ..Default::default()
+                })
+                .unwrap(),
+            );
+        }
+
+        for lines in inputs {
+            let expected = EventQueue::new(lines.clone()).sweep();
+            for slabs in [1, 2, 3, 8, 50] {
+                assert_eq!(sweep(&lines, slabs), expected, "{} slabs", slabs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(sweep(&[], 4).is_empty());
+    }
+}
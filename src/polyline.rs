@@ -0,0 +1,586 @@
+//! Polylines, whose consecutive segments share their end points, with crossings reported per polyline.
+//!
+//! Every crossing names the polyline, the index of the segment within it and the parameter `t` along the segment
+//! for both sides. Crossings of a polyline with itself are kept apart from crossings between different polylines.
+//! Segments which overlap along a line, like a shared edge or a polyline folding back onto itself, have no single
+//! crossing point and are rejected with an [`Overlap`].
+
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::{
+    arc::{self, Curve},
+    direction::Rotation,
+    geometry::{self, round, Line, ParseLineError, Point},
+};
+
+/// A chain of segments through at least two points, closed if the last point repeats the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+}
+
+impl Polyline {
+    /// The segments from each point to the next one.
+    pub fn segments(&self) -> impl Iterator<Item = Line> + '_ {
+        self.points.windows(2).map(|points| Line {
+            p: points[0].clone(),
+            q: points[1].clone(),
+        })
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    /// Closed polylines need at least three segments, the last one ends where the first one starts.
+    pub fn is_closed(&self) -> bool {
+        self.points.len() > 3 && self.points.first() == self.points.last()
+    }
+
+    /// Whether the segments `a < b` share an end point by construction, and are not intersected.
+    fn adjacent(&self, a: usize, b: usize) -> bool {
+        b == a + 1 || (self.is_closed() && a == 0 && b == self.segment_count() - 1)
+    }
+
+    /// Position of a point on one of the segments, an end point is given by the start of the following segment.
+    fn locate(&self, polyline: usize, segment: usize, point: &Point) -> Location {
+        let p = &self.points[segment];
+        let q = &self.points[segment + 1];
+        let t = if *point == p.round(9) {
+            0.0
+        } else if *point == q.round(9) {
+            1.0
+        } else {
+            let (dx, dy) = (q.x - p.x, q.y - p.y);
+            let t = ((point.x - p.x) * dx + (point.y - p.y) * dy) / (dx * dx + dy * dy);
+            round(t.clamp(0.0, 1.0), 9)
+        };
+
+        if t == 1.0 && segment + 1 < self.segment_count() {
+            Location {
+                polyline,
+                segment: segment + 1,
+                t: 0.0,
+            }
+        } else if t == 1.0 && self.is_closed() {
+            Location {
+                polyline,
+                segment: 0,
+                t: 0.0,
+            }
+        } else {
+            Location {
+                polyline,
+                segment,
+                t,
+            }
+        }
+    }
+}
+
+impl Display for Polyline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, point) in self.points.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", point)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Polyline {
+    type Err = ParseLineError;
+
+    /// Parses `x1 y1 x2 y2 ...` with at least two points, the values may be separated by any whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let splits: Vec<_> = s.split_whitespace().collect();
+        if splits.len() < 4 || splits.len() % 2 == 1 {
+            return Err(ParseLineError::NotPolyline {
+                found: splits.len(),
+                column: s.trim_end().chars().count() + 1,
+            });
+        }
+
+        let values = splits
+            .iter()
+            .map(|split| geometry::parse_value(s, split))
+            .collect::<Result<Vec<_>, _>>()?;
+        let points = values
+            .chunks(2)
+            .map(|xy| Point { x: xy[0], y: xy[1] })
+            .collect();
+
+        Ok(Polyline { points })
+    }
+}
+
+/// A point on a polyline, by the index of the polyline, the index of its segment and the parameter `0 <= t < 1`
+/// from the start to the end of the segment. Only the end of an open polyline has `t == 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub polyline: usize,
+    pub segment: usize,
+    pub t: f64,
+}
+
+impl Location {
+    fn key(&self) -> (usize, usize, u64) {
+        (self.polyline, self.segment, self.t.to_bits())
+    }
+}
+
+/// Intersection of two polylines, or of a polyline with itself, with `a` before `b` in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing {
+    /// Rounded to 9 decimals
+    pub point: Point,
+    pub a: Location,
+    pub b: Location,
+}
+
+impl Crossing {
+    fn new(point: Point, a: Location, b: Location) -> Self {
+        let (a, b) = if a.key() <= b.key() { (a, b) } else { (b, a) };
+        Crossing { point, a, b }
+    }
+
+    pub fn is_self_crossing(&self) -> bool {
+        self.a.polyline == self.b.polyline
+    }
+}
+
+/// All crossings, sorted by point and locations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Crossings {
+    pub between: Vec<Crossing>,
+    pub self_crossings: Vec<Crossing>,
+}
+
+impl Crossings {
+    fn new(mut crossings: Vec<Crossing>) -> Self {
+        crossings.sort_by(|x, y| {
+            (&x.point, x.a.key(), x.b.key()).cmp(&(&y.point, y.a.key(), y.b.key()))
+        });
+        // a crossing through a vertex is found for both segments meeting in it
+        crossings.dedup();
+        let (self_crossings, between) = crossings.into_iter().partition(Crossing::is_self_crossing);
+        Crossings {
+            between,
+            self_crossings,
+        }
+    }
+
+    /// The distinct crossing points.
+    pub fn points(&self) -> BTreeSet<Point> {
+        self.between
+            .iter()
+            .chain(&self.self_crossings)
+            .map(|crossing| crossing.point.clone())
+            .collect()
+    }
+}
+
+/// Two segments which overlap along a line instead of crossing in a point, by polyline and segment index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlap {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+}
+
+impl Display for Overlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "segment {} of polyline {} overlaps segment {} of polyline {}",
+            self.a.1, self.a.0, self.b.1, self.b.0
+        )
+    }
+}
+
+impl Error for Overlap {}
+
+/// How two segments of the polylines meet.
+enum Contact {
+    Apart,
+    Point(Point),
+    Overlap,
+}
+
+/// Whether the segments cross, touch or overlap. Consecutive segments of a polyline only meet in their shared vertex,
+/// unless the polyline folds back onto itself.
+fn contact(polylines: &[Polyline], a: &(usize, usize, Line), b: &(usize, usize, Line)) -> Contact {
+    let (polyline, segment, line) = a;
+    let (other_polyline, other_segment, other_line) = b;
+    let (first, second) = (*segment.min(other_segment), *segment.max(other_segment));
+    let adjacent = polyline == other_polyline && polylines[*polyline].adjacent(first, second);
+    if !line.is_collinear(other_line) {
+        return match adjacent {
+            true => Contact::Apart,
+            false => line
+                .intersection(other_line)
+                .map_or(Contact::Apart, Contact::Point),
+        };
+    }
+
+    // compare the collinear segments along the axis in which they are longer
+    let along_x = (line.q.x - line.p.x).abs() >= (line.q.y - line.p.y).abs();
+    let key = |point: &Point| if along_x { point.x } else { point.y };
+    let range = |line: &Line| {
+        let (p, q) = (key(&line.p), key(&line.q));
+        (p.min(q), p.max(q))
+    };
+    let ((min, max), (other_min, other_max)) = (range(line), range(other_line));
+    let (from, to) = (min.max(other_min), max.min(other_max));
+    if from < to {
+        Contact::Overlap
+    } else if from > to || adjacent {
+        Contact::Apart
+    } else {
+        [&line.p, &line.q]
+            .into_iter()
+            .find(|point| key(point) == from)
+            .map_or(Contact::Apart, |point| Contact::Point(point.clone()))
+    }
+}
+
+/// The first pair of consecutive segments of a polyline which fold back onto each other.
+fn fold_back(polylines: &[Polyline]) -> Option<Overlap> {
+    polylines.iter().enumerate().find_map(|(index, polyline)| {
+        let segments: Vec<_> = polyline
+            .segments()
+            .enumerate()
+            .map(|(segment, line)| (index, segment, line))
+            .collect();
+        let last = segments.len() - 1;
+        let pairs = (1..segments.len()).map(|segment| (segment - 1, segment));
+        let closing = polyline.is_closed().then_some((0, last));
+        pairs.chain(closing).find_map(|(a, b)| {
+            matches!(
+                contact(polylines, &segments[a], &segments[b]),
+                Contact::Overlap
+            )
+            .then_some(Overlap {
+                a: (index, a),
+                b: (index, b),
+            })
+        })
+    })
+}
+
+/// The segments of all polylines, by polyline and segment index.
+fn segments(polylines: &[Polyline]) -> Vec<(usize, usize, Line)> {
+    polylines
+        .iter()
+        .enumerate()
+        .flat_map(|(polyline, line)| {
+            line.segments()
+                .enumerate()
+                .map(move |(segment, line)| (polyline, segment, line))
+        })
+        .collect()
+}
+
+/// Crossings of the polylines, found with a sweep over all segments.
+///
+/// Consecutive segments of a polyline are not intersected, other segments which only touch are reported like
+/// crossings. Polylines may have vertical segments, the sweep is then turned by a degree or more, see
+/// [`crate::direction`]. Fails for segments which overlap along a line and panics for repeated consecutive points.
+pub fn crossings(polylines: &[Polyline]) -> Result<Crossings, Overlap> {
+    if let Some(overlap) = fold_back(polylines) {
+        return Err(overlap);
+    }
+    let segments = segments(polylines);
+    if let Some((_, _, line)) = segments.iter().find(|(_, _, line)| line.p == line.q) {
+        panic!("segment of length zero {}", line);
+    }
+    let rotation = (0..360)
+        .map(|degrees| Rotation::from_degrees(degrees as f64))
+        .find(|rotation| {
+            segments.iter().all(|(_, _, line)| {
+                let line = rotation.apply_line(line);
+                line.p.x != line.q.x
+            })
+        })
+        .expect("a direction in which no segment is vertical");
+    let curves: Vec<_> = segments
+        .iter()
+        .map(|(_, _, line)| Curve::Segment(rotation.apply_line(line)))
+        .collect();
+
+    // a vertex on another segment may miss it slightly after the rotation, so intersect the original segments
+    let overlap = RefCell::new(None);
+    let intersection = |a: usize, b: usize| match contact(polylines, &segments[a], &segments[b]) {
+        Contact::Apart => None,
+        Contact::Point(point) => Some(point),
+        Contact::Overlap => {
+            overlap.borrow_mut().get_or_insert(Overlap {
+                a: (segments[a].0, segments[a].1),
+                b: (segments[b].0, segments[b].1),
+            });
+            None
+        }
+    };
+    let intersect = |a, b| {
+        intersection(a, b)
+            .map(|point| rotation.apply(&point))
+            .into_iter()
+            .collect()
+    };
+    let crossings = arc::crossings(&curves, intersect)
+        .into_iter()
+        .map(|(_, a, b)| {
+            let (polyline, segment, _) = &segments[a];
+            let (other_polyline, other_segment, _) = &segments[b];
+            let point = intersection(a, b).expect("the segments intersect").round(9);
+            Crossing::new(
+                point.clone(),
+                polylines[*polyline].locate(*polyline, *segment, &point),
+                polylines[*other_polyline].locate(*other_polyline, *other_segment, &point),
+            )
+        })
+        .collect();
+    match overlap.into_inner() {
+        Some(overlap) => Err(overlap),
+        None => Ok(Crossings::new(crossings)),
+    }
+}
+
+/// Same as [`crossings`], comparing every pair of segments.
+pub fn brute_force(polylines: &[Polyline]) -> Result<Crossings, Overlap> {
+    let segments = segments(polylines);
+    let mut crossings = Vec::new();
+    for (a, first) in segments.iter().enumerate() {
+        for second in &segments[a + 1..] {
+            let (polyline, segment, _) = first;
+            let (other_polyline, other_segment, _) = second;
+            match contact(polylines, first, second) {
+                Contact::Apart => {}
+                Contact::Point(point) => {
+                    let point = point.round(9);
+                    crossings.push(Crossing::new(
+                        point.clone(),
+                        polylines[*polyline].locate(*polyline, *segment, &point),
+                        polylines[*other_polyline].locate(*other_polyline, *other_segment, &point),
+                    ));
+                }
+                Contact::Overlap => {
+                    return Err(Overlap {
+                        a: (*polyline, *segment),
+                        b: (*other_polyline, *other_segment),
+                    })
+                }
+            }
+        }
+    }
+    Ok(Crossings::new(crossings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generate::Rng;
+
+    fn polylines(s: &[&str]) -> Vec<Polyline> {
+        s.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let polyline: Polyline = "0 0\t1 1 2 0".parse().unwrap();
+        assert_eq!(polyline.segment_count(), 2);
+        assert!(!polyline.is_closed());
+        assert_eq!(polyline.to_string().parse(), Ok(polyline));
+
+        let error = "0 0 1 1 2".parse::<Polyline>().unwrap_err();
+        assert_eq!(
+            error,
+            ParseLineError::NotPolyline {
+                found: 5,
+                column: 10
+            }
+        );
+        let error = "0 0 1 x".parse::<Polyline>().unwrap_err();
+        assert_eq!(error.column(), 7);
+        let error = "0 0 1 1 NaN 2".parse::<Polyline>().unwrap_err();
+        assert_eq!(error.column(), 9);
+        assert!("0 0 1 1 0 0 0 0".parse::<Polyline>().unwrap().is_closed());
+    }
+
+    #[test]
+    fn test_self_crossing() {
+        // a bow tie crossing itself in (1, 1), and a vertical line through both of its loops
+        let polylines = polylines(&["0 0 2 2 2 0 0 2 0 0", "0.5 -1 0.5 3"]);
+        let crossings = crossings(&polylines).unwrap();
+
+        assert_eq!(crossings.self_crossings.len(), 1);
+        let crossing = &crossings.self_crossings[0];
+        assert_eq!(crossing.point, Point { x: 1.0, y: 1.0 });
+        assert_eq!(
+            crossing.a,
+            Location {
+                polyline: 0,
+                segment: 0,
+                t: 0.5
+            }
+        );
+        assert_eq!(
+            crossing.b,
+            Location {
+                polyline: 0,
+                segment: 2,
+                t: 0.5
+            }
+        );
+
+        let points: Vec<_> = crossings
+            .between
+            .iter()
+            .map(|crossing| &crossing.point)
+            .collect();
+        assert_eq!(
+            points,
+            [&Point { x: 0.5, y: 0.5 }, &Point { x: 0.5, y: 1.5 }]
+        );
+        assert_eq!(
+            crossings.between[1].a,
+            Location {
+                polyline: 0,
+                segment: 2,
+                t: 0.75
+            }
+        );
+        assert_eq!(
+            crossings.between[1].b,
+            Location {
+                polyline: 1,
+                segment: 0,
+                t: 0.625
+            }
+        );
+    }
+
+    #[test]
+    fn test_shared_vertex() {
+        // the second polyline passes through a vertex of the first one, and ends on it
+        let polylines = polylines(&["0 0 1 1 2 0", "0 3 1 1 2 3", "1 0 1 0.5 1.5 0.5"]);
+        let crossings = crossings(&polylines).unwrap();
+
+        assert!(crossings.self_crossings.is_empty());
+        assert_eq!(crossings.between.len(), 2);
+        let vertex = &crossings.between[0];
+        assert_eq!(vertex.point, Point { x: 1.0, y: 1.0 });
+        assert_eq!(
+            vertex.a,
+            Location {
+                polyline: 0,
+                segment: 1,
+                t: 0.0
+            }
+        );
+        assert_eq!(
+            vertex.b,
+            Location {
+                polyline: 1,
+                segment: 1,
+                t: 0.0
+            }
+        );
+        // the open end of a polyline
+        let end = &crossings.between[1];
+        assert_eq!(
+            end.a,
+            Location {
+                polyline: 0,
+                segment: 1,
+                t: 0.5
+            }
+        );
+        assert_eq!(
+            end.b,
+            Location {
+                polyline: 2,
+                segment: 1,
+                t: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_concurrent() {
+        // six segments through (2, 2), every pair of them is reported
+        let polylines = polylines(&[
+            "0 0 4 4",
+            "0 4 4 0",
+            "0 1 4 3",
+            "0 3 4 1",
+            "0 2.5 2 2 4 1.5",
+            "1 0 2 2 3 0",
+        ]);
+        let crossings = crossings(&polylines).unwrap();
+        assert_eq!(crossings.between.len(), 15);
+        assert_eq!(crossings.points().len(), 1);
+        assert_eq!(crossings, brute_force(&polylines).unwrap());
+    }
+
+    #[test]
+    fn test_same_as_brute_force() {
+        let mut rng = Rng::new(7);
+        let polylines: Vec<_> = (0..40)
+            .map(|_| {
+                let count = 2 + rng.next_u64() as usize % 8;
+                let points = (0..count)
+                    .map(|_| Point {
+                        x: rng.range(0.0, 100.0),
+                        y: rng.range(0.0, 100.0),
+                    })
+                    .collect();
+                Polyline { points }
+            })
+            .collect();
+        let expected = brute_force(&polylines).unwrap();
+        assert!(!expected.self_crossings.is_empty());
+        assert_eq!(crossings(&polylines).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_overlap() {
+        // two polylines sharing an edge, and a polyline folding back onto itself
+        let shared = polylines(&["0 0 1 1 2 0", "2 0 1 1 1 3"]);
+        let overlap = Overlap {
+            a: (0, 1),
+            b: (1, 0),
+        };
+        assert_eq!(crossings(&shared), Err(overlap));
+        assert_eq!(brute_force(&shared), Err(overlap));
+        let folded = polylines(&["0 0 2 0 1 0"]);
+        assert_eq!(
+            crossings(&folded),
+            Err(Overlap {
+                a: (0, 0),
+                b: (0, 1)
+            })
+        );
+        assert_eq!(
+            brute_force(&folded).unwrap_err().to_string(),
+            "segment 0 of polyline 0 overlaps segment 1 of polyline 0"
+        );
+
+        // collinear segments which only touch in an end point cross there, going straight on is no crossing
+        let touching = polylines(&["0 0 1 0", "1 0 2 0 3 0", "2 0 2 1"]);
+        let crossings = crossings(&touching).unwrap();
+        assert_eq!(
+            crossings.points(),
+            BTreeSet::from([Point { x: 1.0, y: 0.0 }, Point { x: 2.0, y: 0.0 }])
+        );
+        assert!(crossings.self_crossings.is_empty());
+        assert_eq!(crossings, brute_force(&touching).unwrap());
+    }
+}
@@ -0,0 +1,247 @@
+//! Rays, infinite lines and arcs next to segments. Rays and lines are clipped to a bounding box so the sweep can
+//! handle them, arcs are swept by [`crate::arc`].
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::{
+    arc::Arc,
+    geometry::{Bounds, Line, ParseLineError, Point},
+};
+
+/// Fraction of the extent by which the bounding box is grown before rays and lines are clipped to it, so their clipped
+/// ends do not land on the points of other primitives on the box.
+pub const CLIP_MARGIN: f64 = 0.01;
+
+/// A segment, a ray or an infinite line, each given by two points `p` and `q`, or a circular arc.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive {
+    /// From `p` to `q`
+    Segment(Line),
+    /// Starts at `p` and passes through `q`
+    Ray(Line),
+    /// Passes through `p` and `q`
+    Line(Line),
+    Arc(Arc),
+}
+
+impl Primitive {
+    /// Bounding box of the defining points of a segment, ray or line, or of the whole arc.
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            Primitive::Segment(line) | Primitive::Ray(line) | Primitive::Line(line) => {
+                Bounds::of_lines([line]).expect("a segment has two points")
+            }
+            Primitive::Arc(arc) => arc.bounds(),
+        }
+    }
+
+    pub fn is_segment(&self) -> bool {
+        matches!(self, Primitive::Segment(_))
+    }
+
+    /// The part of a segment, ray or line inside the bounds, `None` if it misses them and for arcs.
+    ///
+    /// Ends which are defining points of the primitive are kept exactly, so a segment inside the bounds is
+    /// returned unchanged. A ray or line with `p == q` has no direction and is returned as a segment of length zero.
+    pub fn clip(&self, bounds: &Bounds) -> Option<Line> {
+        let (line, mut from, mut to) = match self {
+            Primitive::Segment(line) => (line, 0.0, 1.0),
+            Primitive::Ray(line) => (line, 0.0, f64::INFINITY),
+            Primitive::Line(line) => (line, f64::NEG_INFINITY, f64::INFINITY),
+            Primitive::Arc(_) => return None,
+        };
+        if line.p == line.q {
+            return Some(line.clone());
+        }
+
+        // Liang-Barsky: narrow the parameter range to the slab of each axis
+        let axes = [
+            (line.p.x, line.q.x - line.p.x, bounds.min.x, bounds.max.x),
+            (line.p.y, line.q.y - line.p.y, bounds.min.y, bounds.max.y),
+        ];
+        for (start, delta, min, max) in axes {
+            if delta == 0.0 {
+                if start < min || start > max {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((min - start) / delta, (max - start) / delta);
+            from = f64::max(from, a.min(b));
+            to = f64::min(to, a.max(b));
+        }
+        if from > to {
+            return None;
+        }
+
+        let at = |t: f64| match t {
+            0.0 => line.p.clone(),
+            1.0 => line.q.clone(),
+            t => Point {
+                x: line.p.x + t * (line.q.x - line.p.x),
+                y: line.p.y + t * (line.q.y - line.p.y),
+            },
+        };
+        Some(Line {
+            p: at(from),
+            q: at(to),
+        })
+    }
+}
+
+impl Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Primitive::Segment(line) => write!(f, "{}", line),
+            Primitive::Ray(line) => write!(f, "ray {}", line),
+            Primitive::Line(line) => write!(f, "line {}", line),
+            Primitive::Arc(arc) => write!(f, "arc {}", arc),
+        }
+    }
+}
+
+impl FromStr for Primitive {
+    type Err = ParseLineError;
+
+    /// Parses `x1 y1 x2 y2` as a segment, optionally preceded by `segment`, `ray` or `line`, or `arc cx cy r start end`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim_start();
+        let keyword = trimmed.split_whitespace().next().unwrap_or_default();
+        // blank out the keyword, so the columns of errors still point into `s`
+        let offset = s.len() - trimmed.len() + keyword.len();
+        let values = || format!("{}{}", " ".repeat(offset), &s[offset..]);
+        match keyword {
+            "segment" => Ok(Primitive::Segment(values().parse()?)),
+            "ray" => Ok(Primitive::Ray(values().parse()?)),
+            "line" => Ok(Primitive::Line(values().parse()?)),
+            "arc" => Ok(Primitive::Arc(values().parse()?)),
+            _ => Ok(Primitive::Segment(s.parse()?)),
+        }
+    }
+}
+
+/// Clips all segments, rays and lines to the bounding box of all primitives grown by [`CLIP_MARGIN`], which keeps
+/// the segments unchanged.
+///
+/// Intersections of rays and lines outside of the grown box are lost, arcs are left out.
+pub fn clip_to_bounds(primitives: &[Primitive]) -> Vec<Line> {
+    let corners: Vec<_> = primitives
+        .iter()
+        .map(Primitive::bounds)
+        .flat_map(|bounds| [bounds.min, bounds.max])
+        .collect();
+    let Some(bounds) = Bounds::of_points(&corners) else {
+        return Vec::new();
+    };
+    let bounds = bounds.padded(CLIP_MARGIN);
+    primitives
+        .iter()
+        .filter_map(|primitive| primitive.clip(&bounds))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{brute_force, event_queue::EventQueue};
+
+    fn primitives(s: &[&str]) -> Vec<Primitive> {
+        s.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = primitives(&[
+            "0 1 2 3",
+            "ray 0 1 2 3",
+            " line\t0 1 2 3",
+            "segment 0 1 2 3",
+        ]);
+        let line: Line = "0 1 2 3".parse().unwrap();
+        assert_eq!(parsed[0], Primitive::Segment(line.clone()));
+        assert_eq!(parsed[1], Primitive::Ray(line.clone()));
+        assert_eq!(parsed[2], Primitive::Line(line.clone()));
+        assert_eq!(parsed[3], Primitive::Segment(line));
+        assert_eq!(parsed[1].to_string().parse(), Ok(parsed[1].clone()));
+
+        let error = "ray 0 1 x 3".parse::<Primitive>().unwrap_err();
+        assert_eq!(error.column(), 9);
+        assert!("circle 0 1 2 3".parse::<Primitive>().is_err());
+        let arc = "arc 0 1 2 30 60".parse::<Primitive>().unwrap();
+        assert_eq!(arc.to_string(), "arc 0 1 2 30 60");
+        assert_eq!(arc.clip(&arc.bounds()), None);
+        let error = "arc 0 1 2 30".parse::<Primitive>().unwrap_err();
+        assert_eq!(error.column(), 13);
+        let error = "arc 0 1 -2 30 60".parse::<Primitive>().unwrap_err();
+        assert_eq!(error.column(), 9);
+    }
+
+    #[test]
+    fn test_clip() {
+        let bounds = Bounds {
+            min: Point { x: 0.0, y: 0.0 },
+            max: Point { x: 4.0, y: 2.0 },
+        };
+        let clipped = |s: &str| s.parse::<Primitive>().unwrap().clip(&bounds);
+        let line = |s: &str| Some(s.parse::<Line>().unwrap());
+
+        assert_eq!(clipped("1 1 2 1"), line("1 1 2 1"));
+        assert_eq!(clipped("-2 1 2 1"), line("0 1 2 1"));
+        assert_eq!(clipped("ray 1 1 2 1"), line("1 1 4 1"));
+        assert_eq!(clipped("ray 1 1 0 0"), line("1 1 0 0"));
+        assert_eq!(clipped("line 1 1 2 2"), line("0 0 2 2"));
+        assert_eq!(clipped("line 3 5 3 6"), line("3 0 3 2"));
+        // pointing away from the bounds, or passing them
+        assert_eq!(clipped("ray 5 1 6 1"), None);
+        assert_eq!(clipped("line 0 3 1 3"), None);
+        assert_eq!(clipped("line 5 0 6 2"), None);
+    }
+
+    #[test]
+    fn test_intersections() {
+        let primitives = primitives(&[
+            "0 0 10 10",
+            "line 0 9 1 8.5",
+            "ray 2 0 3 0.6",
+            "ray 9 0.5 8 1",
+            "1 3 7 2",
+        ]);
+        let lines = clip_to_bounds(&primitives);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(primitives[0], Primitive::Segment(lines[0].clone()));
+
+        let expected = brute_force::intersections(&lines);
+        assert_eq!(expected.len(), 7);
+        assert_eq!(EventQueue::new(lines).sweep(), expected);
+    }
+
+    #[test]
+    fn test_clip_through_corner() {
+        // the lines pass through corners of the bounding box which are endpoints of segments
+        for input in [
+            &["0 0 10 1", "2 5 8 4", "line 0 10 1 9", "line 10 10 9 9"][..],
+            &["0 0 10 10", "line 0 10 1 9", "line 0 5 1 5.5"],
+        ] {
+            let lines = clip_to_bounds(&primitives(input));
+            assert_eq!(lines.len(), input.len());
+            assert!(lines[2].p.x < 0.0 && lines[2].q.x > 10.0);
+            let mut events = EventQueue::default();
+            for line in lines {
+                events.try_add_line(line).unwrap();
+            }
+        }
+
+        let lines = clip_to_bounds(&primitives(&[
+            "0 0 10 10",
+            "line 0 10 1 9",
+            "line 0 5 1 5.5",
+        ]));
+        assert_eq!(
+            EventQueue::new(lines.clone()).sweep(),
+            brute_force::intersections(&lines)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt::Write;
+
+use crate::geometry::{Bounds, Line, Point};
+
+/// Minimal SVG builder drawing in data coordinates, with the y axis pointing up.
+pub struct Canvas {
+    bounds: Bounds,
+    margin: f64,
+    body: String,
+}
+
+impl Canvas {
+    pub fn new(bounds: Bounds) -> Self {
+        let margin = bounds.extent() * 0.02;
+        Self {
+            bounds,
+            margin,
+            body: String::new(),
+        }
+    }
+
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    pub fn line(&mut self, line: &Line, color: &str, stroke_width: f64) {
+        self.segment(&line.p, &line.q, color, stroke_width);
+    }
+
+    pub fn segment(&mut self, p: &Point, q: &Point, color: &str, stroke_width: f64) {
+        // y is flipped by the surrounding group, see `finish`
+        writeln!(
+            self.body,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" vector-effect="non-scaling-stroke"/>"#,
+            p.x, p.y, q.x, q.y, color, stroke_width
+        )
+        .unwrap();
+    }
+
+    /// Vertical line spanning the whole drawing, e.g. the sweep line.
+    pub fn vertical(&mut self, x: f64, color: &str, stroke_width: f64) {
+        let low = Point {
+            x,
+            y: self.bounds.min.y - self.margin,
+        };
+        let high = Point {
+            x,
+            y: self.bounds.max.y + self.margin,
+        };
+        self.segment(&low, &high, color, stroke_width);
+    }
+
+    pub fn circle(&mut self, center: &Point, radius: f64, color: &str) {
+        writeln!(
+            self.body,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            center.x, center.y, radius, color
+        )
+        .unwrap();
+    }
+
+    /// Text in screen orientation, anchored at the top left corner of the drawing.
+    pub fn caption(&mut self, text: &str) {
+        let size = self.bounds.extent() * 0.03;
+        writeln!(
+            self.body,
+            r#"<text x="{}" y="{}" font-size="{}" font-family="monospace" transform="scale(1,-1)">{}</text>"#,
+            self.bounds.min.x,
+            -self.bounds.max.y - self.margin + size,
+            size,
+            escape(text)
+        )
+        .unwrap();
+    }
+
+    pub fn finish(self) -> String {
+        let x = self.bounds.min.x - self.margin;
+        let y = -self.bounds.max.y - self.margin;
+        let width = self.bounds.width() + 2.0 * self.margin;
+        let height = self.bounds.height() + 2.0 * self.margin;
+
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="800" height="{}">"#,
+                "\n",
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+                "\n",
+                r#"<g transform="scale(1,-1)">"#,
+                "\n{}</g>\n</svg>\n"
+            ),
+            x,
+            y,
+            width,
+            height,
+            (800.0 * height / width).round(),
+            x,
+            y,
+            width,
+            height,
+            self.body
+        )
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Options for [`render`].
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Stroke width of the segments, in pixels.
+    pub stroke_width: f64,
+    /// Radius of the intersection markers in data units, chosen from the bounding box if not set.
+    pub point_radius: Option<f64>,
+    /// Color every segment by the number of intersections on it, from blue (none) to red (most).
+    pub color_by_count: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            stroke_width: 0.5,
+            point_radius: None,
+            color_by_count: false,
+        }
+    }
+}
+
+/// Renders the segments and their intersections into an SVG fitted to the bounding box of the segments.
+///
+/// `counts` holds the number of intersections of each segment by its position in `lines` and is only used with
+/// [`RenderOptions::color_by_count`].
+pub fn render<'a>(
+    lines: &[Line],
+    intersections: impl IntoIterator<Item = &'a Point>,
+    counts: &[usize],
+    options: &RenderOptions,
+) -> String {
+    let intersections = intersections.into_iter().collect::<Vec<_>>();
+
+    let mut bounds = Bounds::of_lines(lines).unwrap_or(Bounds {
+        min: Point { x: 0.0, y: 0.0 },
+        max: Point { x: 1.0, y: 1.0 },
+    });
+    for point in &intersections {
+        bounds.extend(point);
+    }
+    let radius = options
+        .point_radius
+        .unwrap_or_else(|| bounds.extent() * 0.001);
+
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+    let mut canvas = Canvas::new(bounds);
+    for (id, line) in lines.iter().enumerate() {
+        let color = if options.color_by_count {
+            heat_color(counts.get(id).copied().unwrap_or(0), max_count)
+        } else {
+            "blue".to_string()
+        };
+        canvas.line(line, &color, options.stroke_width);
+    }
+    for point in intersections {
+        canvas.circle(point, radius, "red");
+    }
+
+    canvas.finish()
+}
+
+/// Linear blend from blue for 0 to red for `max`.
+fn heat_color(count: usize, max: usize) -> String {
+    let t = if max == 0 {
+        0.0
+    } else {
+        count as f64 / max as f64
+    };
+    let red = (255.0 * t).round() as u8;
+    let blue = (255.0 * (1.0 - t)).round() as u8;
+    format!("#{:02x}00{:02x}", red, blue)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_heat_color() {
+        assert_eq!(heat_color(0, 0), "#0000ff");
+        assert_eq!(heat_color(0, 4), "#0000ff");
+        assert_eq!(heat_color(4, 4), "#ff0000");
+    }
+
+    #[test]
+    fn test_render() {
+        let lines = vec![
+            Line::from_str("0 1 5 1").unwrap(),
+            Line::from_str("1 0 4 2").unwrap(),
+        ];
+        let point = Point { x: 2.5, y: 1.0 };
+        let counts = [1, 1];
+        let options = RenderOptions {
+            stroke_width: 2.0,
+            point_radius: Some(0.25),
+            color_by_count: true,
+        };
+
+        let svg = render(&lines, [&point], &counts, &options);
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert!(svg.contains(r##"stroke="#ff0000" stroke-width="2""##));
+        assert!(svg.contains(r#"<circle cx="2.5" cy="1" r="0.25" fill="red"/>"#));
+    }
+}
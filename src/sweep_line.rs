@@ -1,9 +1,15 @@
-use crate::geometry::{Line, Point};
+use crate::{
+    arena::{Arena, SegmentId},
+    geometry::Point,
+};
 
-#[derive(Debug, Clone)]
+/// Distance right of an intersection at which two swapped segments are ordered again.
+pub const SWAP_OFFSET: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy)]
 pub struct SweepLineElement {
     pub y: f64,
-    pub line: Line,
+    pub segment: SegmentId,
 }
 
 impl PartialEq for SweepLineElement {
@@ -15,8 +21,7 @@ impl Eq for SweepLineElement {}
 
 impl PartialOrd for SweepLineElement {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        // Reverse order
-        other.y.partial_cmp(&self.y)
+        Some(self.cmp(other))
     }
 }
 
@@ -43,6 +48,12 @@ pub struct SwapResult {
     pub above: Option<SweepLineElement>,
 }
 
+impl Default for SweepLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SweepLine {
     pub fn new() -> Self {
         Self {
@@ -50,14 +61,19 @@ impl SweepLine {
         }
     }
 
-    pub fn insert(&mut self, y: f64, line: Line) {
-        let element = SweepLineElement { y, line };
+    pub fn insert(&mut self, y: f64, segment: SegmentId) {
+        let element = SweepLineElement { y, segment };
         self.elements.push(element);
         self.elements.sort();
     }
 
-    pub fn remove(&mut self, line: &Line) {
-        let index = self.elements.iter().position(|x| x.line == *line);
+    /// Position of the segment, from top to bottom.
+    fn position(&self, segment: SegmentId) -> Option<usize> {
+        self.elements.iter().position(|x| x.segment == segment)
+    }
+
+    pub fn remove(&mut self, segment: SegmentId) {
+        let index = self.position(segment);
         let Some(index) = index else {
             // The line is not in the sweep line
             return;
@@ -66,16 +82,16 @@ impl SweepLine {
         self.elements.remove(index);
     }
 
-    pub fn update(&mut self, x: f64) {
+    pub fn update(&mut self, x: f64, segments: &Arena) {
         // for every line, update the y value to be .y(x)
         for element in self.elements.iter_mut() {
-            element.y = element.line.y(x);
+            element.y = segments[element.segment].y(x);
         }
         self.elements.sort();
     }
 
-    pub fn get_neighbors(&self, line: &Line) -> Option<Neighbors> {
-        let index = self.elements.iter().position(|x| x.line == *line);
+    pub fn get_neighbors(&self, segment: SegmentId) -> Option<Neighbors> {
+        let index = self.position(segment);
         let Some(index) = index else {
             // The line is not in the sweep line
             return None;
@@ -87,12 +103,12 @@ impl SweepLine {
         };
 
         if let Some(line_below) = self.elements.get(index + 1) {
-            neighbors.below = Some(line_below.clone());
+            neighbors.below = Some(*line_below);
         }
 
         if index > 0 {
             if let Some(line_above) = self.elements.get(index - 1) {
-                neighbors.above = Some(line_above.clone());
+                neighbors.above = Some(*line_above);
             }
         }
 
@@ -101,24 +117,30 @@ impl SweepLine {
 
     pub fn swap_and_get_new_neighbors(
         &mut self,
-        line1: &Line,
-        line2: &Line,
+        line1: SegmentId,
+        line2: SegmentId,
         intersection_point: &Point,
+        segments: &Arena,
     ) -> SwapResult {
-        let index_line = self.elements.iter().position(|x| x.line == *line1).unwrap();
-        let index_other_line = self.elements.iter().position(|x| x.line == *line2).unwrap();
+        let index_line = self.position(line1).unwrap();
+        let index_other_line = self.position(line2).unwrap();
 
         if index_line.abs_diff(index_other_line) != 1 {
             panic!(
                 "Two lines with indices too far apart: {}, {}. \nSegments are: {:?}",
-                index_line, index_other_line, self.elements
+                index_line,
+                index_other_line,
+                self.elements
+                    .iter()
+                    .map(|element| &segments[element.segment])
+                    .collect::<Vec<_>>()
             )
         }
 
         // sample the points a bit to the right of the sweep line
-        let delta = 1e-9;
-        self.elements[index_line].y = line1.y(intersection_point.x + delta);
-        self.elements[index_other_line].y = line2.y(intersection_point.x + delta);
+        let delta = SWAP_OFFSET;
+        self.elements[index_line].y = segments[line1].y(intersection_point.x + delta);
+        self.elements[index_other_line].y = segments[line2].y(intersection_point.x + delta);
 
         self.elements.sort();
 
@@ -127,18 +149,18 @@ impl SweepLine {
 
         let mut result = SwapResult {
             below: None,
-            smaller: self.elements[smaller].clone(),
-            bigger: self.elements[bigger].clone(),
+            smaller: self.elements[smaller],
+            bigger: self.elements[bigger],
             above: None,
         };
 
         if let Some(line_above) = self.elements.get(bigger + 1) {
-            result.above = Some(line_above.clone());
+            result.above = Some(*line_above);
         };
 
         if smaller > 0 {
             if let Some(line_below) = self.elements.get(smaller - 1) {
-                result.below = Some(line_below.clone());
+                result.below = Some(*line_below);
             };
         }
 